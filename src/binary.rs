@@ -0,0 +1,541 @@
+//! A compact, versioned binary encoding for **Dag**s, suitable for caching large graphs.
+//!
+//! The encoding begins with a header consisting of the magic bytes `DAGY`, a format version byte,
+//! a flags byte (currently always zero) and the node count, edge count and node index bound. This
+//! is followed by one record per node in topological order, each consisting of:
+//!
+//! - the node's index,
+//! - the node's weight,
+//! - the number of incoming edges, and
+//! - for each incoming edge, the distance back to the parent's record from the previous parent
+//!   (or from this node's record for the first parent) followed by the edge's weight.
+//!
+//! All integers are unsigned LEB128 varints and each weight is a varint byte length followed by
+//! the bytes produced by the user's encoding closure or, with the `serde-1` feature, by `bincode`
+//! via the `*_binary_serde` methods. As parents are sorted by descending
+//! position, every distance is small and non-negative, and because every edge must point back to
+//! an earlier record, a reader can validate acyclicity in a single pass without any search.
+
+use crate::{Dag, NodeIndex};
+use petgraph as pg;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+/// The magic bytes with which every encoding begins.
+pub const MAGIC: [u8; 4] = *b"DAGY";
+
+/// The latest version of the format, written by all writers.
+///
+/// Readers accept any version from `1` up to and including this version.
+pub const VERSION: u8 = 1;
+
+/// An error produced while reading the binary encoding of a **Dag**.
+#[derive(Debug)]
+pub enum BinaryError {
+    /// An error occurred while reading, including reaching the end of the input prematurely.
+    Io(io::Error),
+    /// The input does not begin with the [`MAGIC`] bytes.
+    InvalidMagic,
+    /// The input was written with a newer (or invalid) version of the format.
+    UnsupportedVersion(u8),
+    /// The input is structurally invalid, e.g. a node index is out of bounds or repeated, or the
+    /// number of nodes or edges does not match the header.
+    Corrupt(String),
+    /// The node with the given index has an edge to itself.
+    WouldCycle(usize),
+    /// A weight closure failed to decode a weight.
+    InvalidWeight(String),
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the compact binary encoding of the `Dag` to the given writer.
+    ///
+    /// `node_weight` and `edge_weight` append the encoding of each weight to the given buffer.
+    /// Output is written one record at a time, so the writer need not be buffered.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    /// use std::convert::TryInto;
+    ///
+    /// let mut dag = Dag::<u32, u8>::new();
+    /// let root = dag.add_node(7);
+    /// dag.add_child(root, 1, 8);
+    ///
+    /// let mut bytes = vec![];
+    /// dag.write_binary(
+    ///     &mut bytes,
+    ///     |n, buf| buf.extend(n.to_le_bytes()),
+    ///     |e, buf| buf.push(*e),
+    /// )
+    /// .unwrap();
+    ///
+    /// let read = Dag::<u32, u8>::read_binary(
+    ///     &bytes[..],
+    ///     |b| Ok(u32::from_le_bytes(b.try_into().map_err(|_| "expected 4 bytes")?)),
+    ///     |b| b.first().copied().ok_or_else(|| "expected 1 byte".into()),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(read[root], 7);
+    /// assert_eq!(read.raw_edges()[0].weight, 1);
+    /// ```
+    pub fn write_binary<W, F, G>(&self, writer: W, node_weight: F, edge_weight: G) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&N, &mut Vec<u8>),
+        G: FnMut(&E, &mut Vec<u8>),
+    {
+        self.write_records(writer, infallible(node_weight), infallible(edge_weight))
+    }
+
+    fn write_records<W, F, G>(&self, writer: W, node_weight: F, edge_weight: G) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&N, &mut Vec<u8>) -> io::Result<()>,
+        G: FnMut(&E, &mut Vec<u8>) -> io::Result<()>,
+    {
+        let graph = &self.graph;
+        let source = Source {
+            node_count: graph.node_count(),
+            edge_count: graph.edge_count(),
+            node_bound: graph.node_count(),
+            order: self.topological_order().iter().map(|n| n.index()).collect(),
+            weight: &|n| &graph[NodeIndex::new(n)],
+            parents: |n| {
+                graph
+                    .edges_directed(NodeIndex::new(n), pg::Incoming)
+                    .map(|e| (e.source().index(), e.weight()))
+            },
+        };
+        write(source, writer, node_weight, edge_weight)
+    }
+
+    /// Read a `Dag` from its compact binary encoding, as written by
+    /// [`write_binary`](Dag::write_binary).
+    ///
+    /// `node_weight` and `edge_weight` decode each weight from its bytes. Each record is
+    /// validated as it is read, including that every edge points back to an earlier record, so
+    /// no separate cycle check is required.
+    ///
+    /// Nodes retain their relative index order and, when written by a `Dag`, their exact
+    /// indices. Edges are added in order of their targets' records. The input is read in many
+    /// small pieces, so should be buffered, e.g. with a `std::io::BufReader`.
+    pub fn read_binary<R, F, G>(
+        reader: R,
+        node_weight: F,
+        edge_weight: G,
+    ) -> Result<Self, BinaryError>
+    where
+        R: Read,
+        F: FnMut(&[u8]) -> Result<N, String>,
+        G: FnMut(&[u8]) -> Result<E, String>,
+    {
+        read(reader, node_weight, edge_weight)
+    }
+}
+
+#[cfg(feature = "stable_dag")]
+impl<N, E, Ix> crate::stable_dag::StableDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the compact binary encoding of the `StableDag` to the given writer.
+    ///
+    /// See [`Dag::write_binary`].
+    pub fn write_binary<W, F, G>(&self, writer: W, node_weight: F, edge_weight: G) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&N, &mut Vec<u8>),
+        G: FnMut(&E, &mut Vec<u8>),
+    {
+        self.write_records(writer, infallible(node_weight), infallible(edge_weight))
+    }
+
+    fn write_records<W, F, G>(&self, writer: W, node_weight: F, edge_weight: G) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&N, &mut Vec<u8>) -> io::Result<()>,
+        G: FnMut(&E, &mut Vec<u8>) -> io::Result<()>,
+    {
+        let graph = self.graph();
+        let order = pg::algo::toposort(graph, None).expect("a StableDag never contains a cycle");
+        let source = Source {
+            node_count: graph.node_count(),
+            edge_count: graph.edge_count(),
+            node_bound: pg::visit::NodeIndexable::node_bound(graph),
+            order: order.iter().map(|n| n.index()).collect(),
+            weight: &|n| &graph[NodeIndex::new(n)],
+            parents: |n| {
+                graph
+                    .edges_directed(NodeIndex::new(n), pg::Incoming)
+                    .map(|e| (e.source().index(), e.weight()))
+            },
+        };
+        write(source, writer, node_weight, edge_weight)
+    }
+
+    /// Read a `StableDag` from its compact binary encoding.
+    ///
+    /// Nodes retain their relative index order, however vacant indices are not preserved.
+    ///
+    /// See [`Dag::read_binary`].
+    pub fn read_binary<R, F, G>(
+        reader: R,
+        node_weight: F,
+        edge_weight: G,
+    ) -> Result<Self, BinaryError>
+    where
+        R: Read,
+        F: FnMut(&[u8]) -> Result<N, String>,
+        G: FnMut(&[u8]) -> Result<E, String>,
+    {
+        read(reader, node_weight, edge_weight).map(Self::from)
+    }
+}
+
+#[cfg(feature = "serde-1")]
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the compact binary encoding of the `Dag` to the given writer, encoding each weight
+    /// with `bincode`.
+    ///
+    /// Fails with an error of kind `InvalidData` if a weight cannot be serialized, in which case
+    /// the output is incomplete.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<String, (u8, bool)>::new();
+    /// let root = dag.add_node("root".to_string());
+    /// dag.add_child(root, (1, true), "leaf".to_string());
+    ///
+    /// let mut bytes = vec![];
+    /// dag.write_binary_serde(&mut bytes).unwrap();
+    ///
+    /// let read = Dag::<String, (u8, bool)>::read_binary_serde(&bytes[..]).unwrap();
+    /// assert_eq!(read[root], "root");
+    /// assert_eq!(read.raw_edges()[0].weight, (1, true));
+    /// ```
+    pub fn write_binary_serde<W>(&self, writer: W) -> io::Result<()>
+    where
+        N: serde::Serialize,
+        E: serde::Serialize,
+        W: Write,
+    {
+        self.write_records(writer, encode, encode)
+    }
+
+    /// Read a `Dag` from its compact binary encoding, as written by
+    /// [`write_binary_serde`](Dag::write_binary_serde).
+    ///
+    /// See [`read_binary`](Dag::read_binary).
+    pub fn read_binary_serde<R>(reader: R) -> Result<Self, BinaryError>
+    where
+        N: serde::de::DeserializeOwned,
+        E: serde::de::DeserializeOwned,
+        R: Read,
+    {
+        read(reader, decode, decode)
+    }
+}
+
+#[cfg(all(feature = "serde-1", feature = "stable_dag"))]
+impl<N, E, Ix> crate::stable_dag::StableDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the compact binary encoding of the `StableDag` to the given writer, encoding each
+    /// weight with `bincode`.
+    ///
+    /// See [`Dag::write_binary_serde`].
+    pub fn write_binary_serde<W>(&self, writer: W) -> io::Result<()>
+    where
+        N: serde::Serialize,
+        E: serde::Serialize,
+        W: Write,
+    {
+        self.write_records(writer, encode, encode)
+    }
+
+    /// Read a `StableDag` from its compact binary encoding, as written by
+    /// [`write_binary_serde`](crate::stable_dag::StableDag::write_binary_serde).
+    ///
+    /// Nodes retain their relative index order, however vacant indices are not preserved.
+    ///
+    /// See [`Dag::read_binary`].
+    pub fn read_binary_serde<R>(reader: R) -> Result<Self, BinaryError>
+    where
+        N: serde::de::DeserializeOwned,
+        E: serde::de::DeserializeOwned,
+        R: Read,
+    {
+        read(reader, decode, decode).map(Self::from)
+    }
+}
+
+// Adapt an encoding closure that cannot fail.
+fn infallible<T, F>(mut f: F) -> impl FnMut(&T, &mut Vec<u8>) -> io::Result<()>
+where
+    F: FnMut(&T, &mut Vec<u8>),
+{
+    move |weight, buf| {
+        f(weight, buf);
+        Ok(())
+    }
+}
+
+// Weights use `bincode`'s default options, with varint integers and no trailing bytes.
+#[cfg(feature = "serde-1")]
+fn encode<T: serde::Serialize>(weight: &T, buf: &mut Vec<u8>) -> io::Result<()> {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .serialize_into(buf, weight)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(feature = "serde-1")]
+fn decode<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    use bincode::Options;
+    bincode::DefaultOptions::new()
+        .deserialize(bytes)
+        .map_err(|e| e.to_string())
+}
+
+// The graph to be written, independent of the underlying graph type.
+struct Source<'a, N, P> {
+    node_count: usize,
+    edge_count: usize,
+    node_bound: usize,
+    // Node indices in topological order.
+    order: Vec<usize>,
+    weight: &'a dyn Fn(usize) -> &'a N,
+    // The parents of a node paired with the weights of their edges.
+    parents: P,
+}
+
+fn write<'a, N, E: 'a, P, I, W, F, G>(
+    source: Source<'a, N, P>,
+    mut writer: W,
+    mut node_weight: F,
+    mut edge_weight: G,
+) -> io::Result<()>
+where
+    P: Fn(usize) -> I,
+    I: Iterator<Item = (usize, &'a E)>,
+    W: Write,
+    F: FnMut(&N, &mut Vec<u8>) -> io::Result<()>,
+    G: FnMut(&E, &mut Vec<u8>) -> io::Result<()>,
+{
+    let mut position = vec![0; source.node_bound];
+    for (p, &n) in source.order.iter().enumerate() {
+        position[n] = p;
+    }
+
+    let mut record = vec![];
+    record.extend_from_slice(&MAGIC);
+    record.push(VERSION);
+    record.push(0);
+    write_varint(&mut record, source.node_count as u64);
+    write_varint(&mut record, source.edge_count as u64);
+    write_varint(&mut record, source.node_bound as u64);
+    writer.write_all(&record)?;
+
+    let mut buf = vec![];
+    let mut parents = vec![];
+    for (p, &n) in source.order.iter().enumerate() {
+        record.clear();
+        write_varint(&mut record, n as u64);
+        buf.clear();
+        node_weight((source.weight)(n), &mut buf)?;
+        write_bytes(&mut record, &buf);
+
+        parents.clear();
+        parents.extend((source.parents)(n).map(|(parent, w)| (position[parent], w)));
+        parents.sort_by_key(|&(q, _)| std::cmp::Reverse(q));
+        write_varint(&mut record, parents.len() as u64);
+        let mut prev = p;
+        for &(q, weight) in &parents {
+            write_varint(&mut record, (prev - q) as u64);
+            prev = q;
+            buf.clear();
+            edge_weight(weight, &mut buf)?;
+            write_bytes(&mut record, &buf);
+        }
+        writer.write_all(&record)?;
+    }
+    Ok(())
+}
+
+fn read<N, E, Ix, R, F, G>(
+    mut reader: R,
+    mut node_weight: F,
+    mut edge_weight: G,
+) -> Result<Dag<N, E, Ix>, BinaryError>
+where
+    Ix: IndexType,
+    R: Read,
+    F: FnMut(&[u8]) -> Result<N, String>,
+    G: FnMut(&[u8]) -> Result<E, String>,
+{
+    let mut header = [0; 6];
+    reader.read_exact(&mut header)?;
+    if header[..4] != MAGIC {
+        return Err(BinaryError::InvalidMagic);
+    }
+    let version = header[4];
+    if version == 0 || version > VERSION {
+        return Err(BinaryError::UnsupportedVersion(version));
+    }
+    if header[5] != 0 {
+        return Err(corrupt(format!("unknown flags {:#x}", header[5])));
+    }
+    let node_count = read_usize(&mut reader)?;
+    let edge_count = read_usize(&mut reader)?;
+    let bound = read_usize(&mut reader)?;
+    let max = <Ix as IndexType>::max().index();
+    if node_count >= max || edge_count >= max || bound > max || node_count > bound {
+        return Err(corrupt("invalid node or edge count"));
+    }
+
+    // Don't trust the header's counts or the node indices when reserving memory up front, so
+    // nodes and edges are recorded by the position of their records until the input is read.
+    const MAX_RESERVE: usize = 1 << 16;
+    let mut order = Vec::with_capacity(node_count.min(MAX_RESERVE));
+    let mut weights = Vec::with_capacity(node_count.min(MAX_RESERVE));
+    let mut edges = Vec::with_capacity(edge_count.min(MAX_RESERVE));
+    let mut buf = vec![];
+    for p in 0..node_count {
+        let index = read_usize(&mut reader)?;
+        if index >= bound {
+            return Err(corrupt(format!("node index {} is out of bounds", index)));
+        }
+        read_bytes(&mut reader, &mut buf)?;
+        let weight = node_weight(&buf).map_err(BinaryError::InvalidWeight)?;
+
+        let parent_count = read_usize(&mut reader)?;
+        let mut q = p;
+        for j in 0..parent_count {
+            let distance = read_usize(&mut reader)?;
+            if j == 0 && distance == 0 {
+                return Err(BinaryError::WouldCycle(index));
+            }
+            if distance > q {
+                return Err(corrupt(format!("node {} has a parent out of range", index)));
+            }
+            q -= distance;
+            if edges.len() == edge_count {
+                return Err(corrupt("more edges than declared"));
+            }
+            read_bytes(&mut reader, &mut buf)?;
+            let weight = edge_weight(&buf).map_err(BinaryError::InvalidWeight)?;
+            edges.push((q, p, weight));
+        }
+        weights.push(Some(weight));
+        order.push(index);
+    }
+    if edges.len() != edge_count {
+        return Err(corrupt("fewer edges than declared"));
+    }
+
+    // Add the nodes in order of their indices, skipping any vacant indices.
+    let mut by_index: Vec<usize> = (0..node_count).collect();
+    by_index.sort_unstable_by_key(|&p| order[p]);
+    if let Some(w) = by_index.windows(2).find(|w| order[w[0]] == order[w[1]]) {
+        return Err(corrupt(format!("node index {} is repeated", order[w[0]])));
+    }
+    // Every edge points to an earlier record, so the graph is acyclic by construction.
+    let mut dag = Dag::with_capacity(node_count, edge_count);
+    let mut indices = vec![NodeIndex::end(); node_count];
+    for p in by_index {
+        let weight = weights[p].take().expect("each record is added once");
+        indices[p] = dag.graph.add_node(weight);
+    }
+    for (q, p, weight) in edges {
+        dag.graph.add_edge(indices[q], indices[p], weight);
+    }
+    Ok(dag)
+}
+
+fn corrupt(message: impl Into<String>) -> BinaryError {
+    BinaryError::Corrupt(message.into())
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> Result<u64, BinaryError> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        let bits = u64::from(byte[0] & 0x7f);
+        if shift == 63 && bits > 1 {
+            break;
+        }
+        n |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(n);
+        }
+    }
+    Err(corrupt("varint overflows 64 bits"))
+}
+
+fn read_usize<R: Read>(reader: &mut R) -> Result<usize, BinaryError> {
+    let n = read_varint(reader)?;
+    usize::try_from(n).map_err(|_| corrupt(format!("{} does not fit in usize", n)))
+}
+
+// Read a length-prefixed byte string into `buf`, without trusting the length for allocation.
+fn read_bytes<R: Read>(reader: &mut R, buf: &mut Vec<u8>) -> Result<(), BinaryError> {
+    let len = read_varint(reader)?;
+    buf.clear();
+    reader.by_ref().take(len).read_to_end(buf)?;
+    if (buf.len() as u64) < len {
+        return Err(BinaryError::Io(io::ErrorKind::UnexpectedEof.into()));
+    }
+    Ok(())
+}
+
+impl From<io::Error> for BinaryError {
+    fn from(err: io::Error) -> Self {
+        BinaryError::Io(err)
+    }
+}
+
+impl std::fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BinaryError::Io(err) => write!(f, "failed to read binary dag: {}", err),
+            BinaryError::InvalidMagic => write!(f, "input is not a binary dag"),
+            BinaryError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            BinaryError::Corrupt(message) => write!(f, "corrupt binary dag: {}", message),
+            BinaryError::WouldCycle(node) => write!(f, "node {} has an edge to itself", node),
+            BinaryError::InvalidWeight(message) => write!(f, "invalid weight: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for BinaryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinaryError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
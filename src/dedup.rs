@@ -0,0 +1,148 @@
+//! Common-subexpression elimination for **Dag**s via hash-consing.
+
+use crate::{Dag, NodeIndex};
+use petgraph as pg;
+use petgraph::graph::{DiGraph, IndexType};
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Merge all structurally identical sub-DAGs, so that each distinct sub-DAG occurs once.
+    ///
+    /// Two nodes are merged when their weights are equal according to `node_eq` and they have the
+    /// same children (after those children have themselves been merged) via edges of equal weight.
+    /// Nodes are visited bottom-up in reverse topological order and grouped by the hash of their
+    /// merged children, so `node_eq` is only called for nodes with identical children. Of each
+    /// group of equal nodes, the node with the lowest index is kept and the edges from the parents
+    /// of the others are rewired to it.
+    ///
+    /// Computes in **O(n + e log e)** expected time plus **O(n d)** calls to `node_eq`, where `d`
+    /// is the greatest number of distinct nodes sharing the same children. As all leaves share the
+    /// same (empty) children, prefer
+    /// [`dedup_subgraphs_by_key`](Dag::dedup_subgraphs_by_key) where weights can be hashed.
+    ///
+    /// Merging can never create a cycle, so no cycle checks are required.
+    ///
+    /// The remaining nodes and edges retain their relative order, however their indices are
+    /// compacted. Returns a table mapping each node's old index to its new index (the new index
+    /// of the node it was merged into, if any) along with the number of nodes eliminated.
+    pub fn dedup_subgraphs<F>(&mut self, node_eq: F) -> (Vec<NodeIndex<Ix>>, usize)
+    where
+        E: PartialEq,
+        F: FnMut(&N, &N) -> bool,
+    {
+        self.dedup_by(|_| (), node_eq)
+    }
+
+    /// Merge all structurally identical sub-DAGs, identifying equal weights by the key returned
+    /// by `node_key`.
+    ///
+    /// This is equivalent to [`dedup_subgraphs`](Dag::dedup_subgraphs), however nodes are also
+    /// grouped by the hash of their key, so edge weights are only compared between nodes whose
+    /// keys and children are identical. Computes in **O(n + e log e)** expected time plus the
+    /// cost of the `node_key` calls, except that each node is also compared with every node that
+    /// shares its key and children but not its edge weights.
+    pub fn dedup_subgraphs_by_key<K, F>(&mut self, node_key: F) -> (Vec<NodeIndex<Ix>>, usize)
+    where
+        E: PartialEq,
+        K: Eq + Hash,
+        F: FnMut(&N) -> K,
+    {
+        self.dedup_by(node_key, |_, _| true)
+    }
+
+    // Merge nodes with equal keys, equal weights and the same merged children.
+    fn dedup_by<K, F, G>(&mut self, mut node_key: F, mut node_eq: G) -> (Vec<NodeIndex<Ix>>, usize)
+    where
+        E: PartialEq,
+        K: Eq + Hash,
+        F: FnMut(&N) -> K,
+        G: FnMut(&N, &N) -> bool,
+    {
+        // Each node's class is identified by the index of the first member visited.
+        let mut class: Vec<NodeIndex<Ix>> = (0..self.node_count()).map(NodeIndex::new).collect();
+        // Each class's representative along with its merged children, by key and children.
+        let mut buckets: HashMap<_, Vec<(NodeIndex<Ix>, Vec<_>)>> = HashMap::new();
+        for node in self.topological_order().into_iter().rev() {
+            let children = self.merged_children(node, &class);
+            let key = (
+                node_key(&self.graph[node]),
+                children.iter().map(|&(c, _)| c).collect::<Vec<_>>(),
+            );
+            let bucket = buckets.entry(key).or_default();
+            let found = bucket.iter().find(|(rep, rep_children)| {
+                node_eq(&self.graph[node], &self.graph[*rep]) && same_edges(&children, rep_children)
+            });
+            match found {
+                Some(&(rep, _)) => class[node.index()] = rep,
+                None => bucket.push((node, children)),
+            }
+        }
+
+        // Keep the member of each class with the lowest index.
+        let mut keeper = (0..self.node_count()).collect::<Vec<_>>();
+        for (i, c) in class.iter().enumerate() {
+            keeper[c.index()] = keeper[c.index()].min(i);
+        }
+        let is_kept = |i: usize| keeper[class[i].index()] == i;
+        let kept = (0..self.node_count()).filter(|&i| is_kept(i)).count();
+        let eliminated = self.node_count() - kept;
+        let old_graph = std::mem::replace(&mut self.graph, DiGraph::with_capacity(0, 0));
+        let (nodes, edges) = old_graph.into_nodes_edges();
+        let mut graph = DiGraph::with_capacity(kept, edges.len());
+        let mut new_index = vec![None; nodes.len()];
+        for (i, node) in nodes.into_iter().enumerate() {
+            if is_kept(i) {
+                new_index[i] = Some(graph.add_node(node.weight));
+            }
+        }
+        let remap: Vec<NodeIndex<Ix>> = class
+            .iter()
+            .map(|c| new_index[keeper[c.index()]].expect("keeper was kept"))
+            .collect();
+        for edge in edges {
+            let source = edge.source().index();
+            if is_kept(source) {
+                graph.add_edge(remap[source], remap[edge.target().index()], edge.weight);
+            }
+        }
+        self.graph = graph;
+        (remap, eliminated)
+    }
+
+    // The node's outgoing edges as (merged child index, edge weight), sorted by child.
+    fn merged_children(&self, node: NodeIndex<Ix>, class: &[NodeIndex<Ix>]) -> Vec<(usize, &E)> {
+        let mut children: Vec<_> = self
+            .graph
+            .edges_directed(node, pg::Outgoing)
+            .map(|e| (class[e.target().index()].index(), e.weight()))
+            .collect();
+        children.sort_by_key(|&(c, _)| c);
+        children
+    }
+}
+
+// Whether the two sorted edge lists contain the same weights to each child.
+fn same_edges<E: PartialEq>(a: &[(usize, &E)], b: &[(usize, &E)]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut matched = vec![false; b.len()];
+    a.iter().all(|&(child, weight)| {
+        let start = b.partition_point(|&(c, _)| c < child);
+        let found = (start..b.len())
+            .take_while(|&j| b[j].0 == child)
+            .find(|&j| !matched[j] && *b[j].1 == *weight);
+        match found {
+            Some(j) => {
+                matched[j] = true;
+                true
+            }
+            None => false,
+        }
+    })
+}
@@ -0,0 +1,341 @@
+//! Importing Make-style dependency files, e.g. the `.d` files written by `gcc -MD` and read by
+//! Ninja's `depfile` binding, as a **Dag** of paths.
+//!
+//! Each rule of the form `targets: prerequisites` adds an edge from every prerequisite to every
+//! target, so that a topological order of the **Dag** is a valid build order. Rules may span
+//! multiple lines via a trailing `\`, and rules with the same target, whether in the same file or
+//! in different files, are merged.
+//!
+//! Paths are unescaped following the same rules as Ninja:
+//!
+//! - `\ ` (or, generally, an odd number of backslashes before a space) is a space within a path,
+//!   preceded by half as many backslashes. An even number of backslashes before a space are kept
+//!   as they are, and the space ends the path.
+//! - `\#` is a `#`, whereas an unescaped `#` begins a comment that continues to the end of the line.
+//! - `$$` is a `$`.
+//! - A `:` only separates targets from prerequisites when followed by whitespace or the end of the
+//!   line, so Windows paths such as `C:\src\main.c` are read as-is.
+//! - Any other backslash is kept as part of the path.
+//!
+//! Order-only prerequisites following a `|` are treated like any other prerequisite.
+
+use crate::{Dag, NodeIndex};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A **Dag** of paths built by merging the rules of any number of dependency files.
+#[derive(Clone, Debug, Default)]
+pub struct Depfiles {
+    dag: Dag<PathBuf, ()>,
+    indices: HashMap<PathBuf, NodeIndex>,
+}
+
+/// An error produced while reading a dependency file.
+#[derive(Debug)]
+pub enum DepfileError {
+    /// The file could not be read.
+    Io {
+        /// The path of the file.
+        file: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// The file is not a valid dependency file.
+    Syntax {
+        /// The path of the file.
+        file: PathBuf,
+        /// The line on which the error occurred, starting from 1.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// A rule would have made a path depend on itself, directly or indirectly.
+    WouldCycle {
+        /// The path of the file.
+        file: PathBuf,
+        /// The line on which the prerequisite closing the cycle appears, starting from 1.
+        line: usize,
+        /// The prerequisite of the edge that would have closed the cycle.
+        prerequisite: PathBuf,
+        /// The target of the edge that would have closed the cycle.
+        target: PathBuf,
+    },
+}
+
+// A single path within a rule, along with the line on which it appears.
+struct Word {
+    path: String,
+    line: usize,
+}
+
+// The targets and prerequisites of a single rule.
+struct Rule {
+    targets: Vec<Word>,
+    prerequisites: Vec<Word>,
+}
+
+/// Parse a single dependency file into a `Dag`.
+///
+/// `file` is only used to identify the file within any error. See [`Depfiles`] for merging the
+/// rules of several files into a single `Dag`.
+///
+/// ```rust
+/// use daggy::depfile;
+/// use std::path::Path;
+///
+/// let d = "main.o: main.c util.h \\\n  my\\ header.h\nutil.h:\n";
+/// let dag = depfile::parse("main.d", d).unwrap();
+/// assert_eq!(dag.node_count(), 4);
+/// assert_eq!(dag.raw_nodes()[3].weight, Path::new("my header.h"));
+/// assert_eq!(dag.edge_count(), 3);
+/// ```
+pub fn parse<P>(file: P, contents: &str) -> Result<Dag<PathBuf, ()>, DepfileError>
+where
+    P: AsRef<Path>,
+{
+    let mut depfiles = Depfiles::new();
+    depfiles.parse(file, contents)?;
+    Ok(depfiles.into_dag())
+}
+
+impl Depfiles {
+    /// Create a new, empty `Depfiles`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Borrow the inner `Dag`.
+    pub fn dag(&self) -> &Dag<PathBuf, ()> {
+        &self.dag
+    }
+
+    /// Take ownership of the `Depfiles` and return the inner `Dag`.
+    pub fn into_dag(self) -> Dag<PathBuf, ()> {
+        self.dag
+    }
+
+    /// The index of the node for the given path, if it appears in any of the files.
+    pub fn node(&self, path: &Path) -> Option<NodeIndex> {
+        self.indices.get(path).cloned()
+    }
+
+    /// Read the dependency file at the given path and merge its rules.
+    ///
+    /// See [`Depfiles::parse`].
+    pub fn read<P>(&mut self, path: P) -> Result<(), DepfileError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => self.parse(path, &contents),
+            Err(error) => Err(DepfileError::Io {
+                file: path.to_path_buf(),
+                error,
+            }),
+        }
+    }
+
+    /// Parse the contents of a dependency file and merge its rules.
+    ///
+    /// Paths that have already been seen refer to the same node, and an edge that already exists
+    /// is not added again. `file` is only used to identify the file within any error.
+    ///
+    /// If the file is invalid or its rules would create a cycle, an error is returned and the
+    /// `Dag` is left unchanged.
+    pub fn parse<P>(&mut self, file: P, contents: &str) -> Result<(), DepfileError>
+    where
+        P: AsRef<Path>,
+    {
+        let file = file.as_ref();
+        let rules = parse_rules(contents).map_err(|(line, message)| DepfileError::Syntax {
+            file: file.to_path_buf(),
+            line,
+            message: message.to_string(),
+        })?;
+
+        let node_count = self.dag.node_count();
+        let edge_count = self.dag.edge_count();
+        for rule in rules {
+            let targets: Vec<_> = rule
+                .targets
+                .iter()
+                .map(|t| self.add_path(&t.path))
+                .collect();
+            for prerequisite in &rule.prerequisites {
+                let a = self.add_path(&prerequisite.path);
+                for &b in &targets {
+                    if self.dag.update_edge(a, b, ()).is_err() {
+                        let err = DepfileError::WouldCycle {
+                            file: file.to_path_buf(),
+                            line: prerequisite.line,
+                            prerequisite: self.dag[a].clone(),
+                            target: self.dag[b].clone(),
+                        };
+                        self.truncate(node_count, edge_count);
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The node for the given path, adding it if necessary.
+    fn add_path(&mut self, path: &str) -> NodeIndex {
+        let dag = &mut self.dag;
+        *self
+            .indices
+            .entry(PathBuf::from(path))
+            .or_insert_with_key(|path| dag.add_node(path.clone()))
+    }
+
+    // Remove all nodes and edges added since the graph had the given counts.
+    //
+    // New edges and nodes always occupy the highest indices, so removing them from the last index
+    // down leaves all earlier indices untouched.
+    fn truncate(&mut self, node_count: usize, edge_count: usize) {
+        while self.dag.edge_count() > edge_count {
+            let e = crate::EdgeIndex::new(self.dag.edge_count() - 1);
+            self.dag.remove_edge(e);
+        }
+        while self.dag.node_count() > node_count {
+            let n = NodeIndex::new(self.dag.node_count() - 1);
+            if let Some(path) = self.dag.remove_node(n) {
+                self.indices.remove(&path);
+            }
+        }
+    }
+}
+
+// Split the contents into rules, returning the line and a description of any syntax error.
+fn parse_rules(contents: &str) -> Result<Vec<Rule>, (usize, &'static str)> {
+    let mut rules = vec![];
+    let mut words: Vec<Word> = vec![];
+    // The number of target words on the current logical line, once its `:` has been seen.
+    let mut colon: Option<usize> = None;
+    let mut word = String::new();
+    let mut line = 1;
+    let mut chars = contents.chars().peekable();
+
+    fn end_word(word: &mut String, words: &mut Vec<Word>, line: usize) {
+        if !word.is_empty() {
+            let path = std::mem::take(word);
+            words.push(Word { path, line });
+        }
+    }
+
+    loop {
+        let c = chars.next();
+        match c {
+            None | Some('\n') => {
+                end_word(&mut word, &mut words, line);
+                match colon.take() {
+                    Some(n) => {
+                        let prerequisites = words.split_off(n);
+                        let targets = std::mem::take(&mut words);
+                        rules.push(Rule {
+                            targets,
+                            prerequisites,
+                        });
+                    }
+                    None if words.is_empty() => (),
+                    None => return Err((words[0].line, "expected `:` after targets")),
+                }
+                if c.is_none() {
+                    return Ok(rules);
+                }
+                line += 1;
+            }
+            Some(' ') | Some('\t') | Some('\r') => end_word(&mut word, &mut words, line),
+            Some('\\') => {
+                let mut n = 1;
+                while chars.peek() == Some(&'\\') {
+                    chars.next();
+                    n += 1;
+                }
+                match chars.peek() {
+                    Some(&c @ ' ') | Some(&c @ '#') if n % 2 == 1 => {
+                        chars.next();
+                        word.push_str(&"\\".repeat(n / 2));
+                        word.push(c);
+                    }
+                    Some('\n') | Some('\r') => {
+                        // A line continuation, possibly preceded by backslashes within the path.
+                        word.push_str(&"\\".repeat(n - 1));
+                        end_word(&mut word, &mut words, line);
+                        if chars.next() == Some('\r') && chars.peek() == Some(&'\n') {
+                            chars.next();
+                        }
+                        line += 1;
+                    }
+                    _ => word.push_str(&"\\".repeat(n)),
+                }
+            }
+            Some('$') if chars.peek() == Some(&'$') => {
+                chars.next();
+                word.push('$');
+            }
+            Some('#') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            Some(':') if matches!(chars.peek(), None | Some(' ' | '\t' | '\r' | '\n')) => {
+                end_word(&mut word, &mut words, line);
+                if colon.is_some() {
+                    return Err((line, "unexpected second `:`"));
+                }
+                if words.is_empty() {
+                    return Err((line, "expected a target before `:`"));
+                }
+                colon = Some(words.len());
+            }
+            Some('|') if word.is_empty() && colon.is_some() => {
+                // The start of order-only prerequisites, unless part of a path.
+                if !matches!(chars.peek(), None | Some(' ' | '\t' | '\r' | '\n')) {
+                    word.push('|');
+                }
+            }
+            Some(c) => word.push(c),
+        }
+    }
+}
+
+impl std::fmt::Display for DepfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DepfileError::Io { file, error } => {
+                write!(f, "failed to read {}: {}", file.display(), error)
+            }
+            DepfileError::Syntax {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file.display(), line, message),
+            DepfileError::WouldCycle {
+                file,
+                line,
+                prerequisite,
+                target,
+            } => write!(
+                f,
+                "{}:{}: dependency of {} on {} would create a cycle",
+                file.display(),
+                line,
+                target.display(),
+                prerequisite.display(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DepfileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DepfileError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
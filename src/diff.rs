@@ -0,0 +1,453 @@
+//! Structural diffs between two **Dag**s and applying them as patches.
+//!
+//! Nodes are matched between graphs by a user-provided key rather than by index, so that two
+//! **Dag**s built in a different order (or with different removal histories) may still be
+//! compared. Edges are identified by the keys of their endpoints, along with their position among
+//! any parallel edges between the same pair of nodes.
+
+use crate::transaction::TransactionError;
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph::graph::IndexType;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The differences between two **Dag**s, as produced by [`diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagDiff<K, N, E> {
+    /// Nodes that only exist in the new **Dag**.
+    pub added_nodes: Vec<(K, N)>,
+    /// Nodes that only exist in the old **Dag**.
+    pub removed_nodes: Vec<(K, N)>,
+    /// Nodes whose weight differs between the two **Dag**s.
+    pub modified_nodes: Vec<NodeChange<K, N>>,
+    /// Edges that only exist in the new **Dag**.
+    pub added_edges: Vec<EdgeDiff<K, E>>,
+    /// Edges that only exist in the old **Dag**.
+    pub removed_edges: Vec<EdgeDiff<K, E>>,
+    /// Edges whose weight differs between the two **Dag**s.
+    pub modified_edges: Vec<EdgeChange<K, E>>,
+}
+
+/// A change to the weight of the node with the given key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeChange<K, N> {
+    /// The key of the modified node.
+    pub key: K,
+    /// The weight of the node within the old **Dag**.
+    pub old: N,
+    /// The weight of the node within the new **Dag**.
+    pub new: N,
+}
+
+/// An edge between the nodes with the given keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeDiff<K, E> {
+    /// The key of the edge's source node.
+    pub source: K,
+    /// The key of the edge's target node.
+    pub target: K,
+    /// The position of the edge among the edges between the same pair of nodes, in order of
+    /// index, within the **Dag** in which it exists.
+    pub occurrence: usize,
+    /// The weight of the edge.
+    pub weight: E,
+}
+
+/// A change to the weight of the edge between the nodes with the given keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EdgeChange<K, E> {
+    /// The key of the edge's source node.
+    pub source: K,
+    /// The key of the edge's target node.
+    pub target: K,
+    /// The position of the edge among the edges between the same pair of nodes, in order of
+    /// index, within the old **Dag**.
+    pub occurrence: usize,
+    /// The weight of the edge within the old **Dag**.
+    pub old: E,
+    /// The weight of the edge within the new **Dag**.
+    pub new: E,
+}
+
+/// The reason a [`DagDiff`] could not be applied to a **Dag**.
+///
+/// When returned, the **Dag** is left unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchError<K> {
+    /// A node to be added already exists.
+    NodeExists(K),
+    /// A node to be removed or modified does not exist.
+    MissingNode(K),
+    /// A node's weight does not match the old weight recorded by the diff.
+    NodeModified(K),
+    /// A node to be removed still has edges that the diff does not remove.
+    NodeHasEdges(K),
+    /// An edge to be removed or modified does not exist between the given nodes.
+    MissingEdge(K, K),
+    /// The weight of an edge between the given nodes does not match the old weight recorded by
+    /// the diff.
+    EdgeModified(K, K),
+    /// Applying the diff would have created a cycle.
+    WouldCycle,
+}
+
+impl<K, N, E> DagDiff<K, N, E> {
+    /// Whether or not the diff contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.modified_edges.is_empty()
+    }
+}
+
+/// Produce the differences between the `old` and `new` **Dag**s.
+///
+/// Nodes are matched by the key returned by `node_key` and are considered modified if their
+/// weights are not equal. Edges are matched by the keys of their endpoints and are considered
+/// modified if `edge_eq` returns `false`. Parallel edges between the same pair of nodes are first
+/// matched with equal edges and then with any remaining edges, in order of their indices, so that
+/// removing one of several parallel edges is reported as a removal of that edge.
+///
+/// All changes are listed in order of the indices within the **Dag** in which they appear.
+///
+/// **Panics** if two nodes within the same **Dag** share a key.
+pub fn diff<N, E, Ix, K, F, G>(
+    old: &Dag<N, E, Ix>,
+    new: &Dag<N, E, Ix>,
+    mut node_key: F,
+    mut edge_eq: G,
+) -> DagDiff<K, N, E>
+where
+    N: Clone + PartialEq,
+    E: Clone,
+    Ix: IndexType,
+    K: Clone + Eq + Hash,
+    F: FnMut(&N) -> K,
+    G: FnMut(&E, &E) -> bool,
+{
+    let old_keys = node_keys(old, &mut node_key);
+    let new_keys = node_keys(new, &mut node_key);
+    let old_indices = key_indices::<K, Ix>(&old_keys);
+    let new_indices = key_indices::<K, Ix>(&new_keys);
+
+    let mut diff = DagDiff {
+        added_nodes: vec![],
+        removed_nodes: vec![],
+        modified_nodes: vec![],
+        added_edges: vec![],
+        removed_edges: vec![],
+        modified_edges: vec![],
+    };
+
+    for (key, node) in old_keys.iter().zip(old.raw_nodes()) {
+        match new_indices.get(key) {
+            None => diff.removed_nodes.push((key.clone(), node.weight.clone())),
+            Some(&n) if new[n] != node.weight => diff.modified_nodes.push(NodeChange {
+                key: key.clone(),
+                old: node.weight.clone(),
+                new: new[n].clone(),
+            }),
+            Some(_) => (),
+        }
+    }
+    for (key, node) in new_keys.iter().zip(new.raw_nodes()) {
+        if !old_indices.contains_key(key) {
+            diff.added_nodes.push((key.clone(), node.weight.clone()));
+        }
+    }
+
+    let old_edges = edge_groups(old, &old_keys);
+    let new_edges = edge_groups(new, &new_keys);
+    let mut matches = HashMap::with_capacity(old_edges.len());
+    for (pair, old_group) in &old_edges {
+        let new_group = new_edges.get(pair).map_or(&[][..], |es| &es[..]);
+        let matched = match_parallel(old_group, new_group, |a, b| edge_eq(&old[a], &new[b]));
+        matches.insert(pair, matched);
+    }
+    let mut occurrences = HashMap::new();
+    for edge in old.raw_edges() {
+        let pair = endpoint_keys(&old_keys, edge.source(), edge.target());
+        let nth = occurrences.entry(pair.clone()).or_insert(0);
+        let occurrence = *nth;
+        let matching = matches[&pair].0[occurrence].map(|j| &new_edges[&pair][j]);
+        *nth += 1;
+        let (source, target) = pair;
+        match matching {
+            None => diff.removed_edges.push(EdgeDiff {
+                source,
+                target,
+                occurrence,
+                weight: edge.weight.clone(),
+            }),
+            Some(&e) if !edge_eq(&edge.weight, &new[e]) => diff.modified_edges.push(EdgeChange {
+                source,
+                target,
+                occurrence,
+                old: edge.weight.clone(),
+                new: new[e].clone(),
+            }),
+            Some(_) => (),
+        }
+    }
+    occurrences.clear();
+    for edge in new.raw_edges() {
+        let pair = endpoint_keys(&new_keys, edge.source(), edge.target());
+        let nth = occurrences.entry(pair.clone()).or_insert(0);
+        let matched = match matches.get(&pair) {
+            Some((_, m)) => m[*nth],
+            None => false,
+        };
+        if !matched {
+            let (source, target) = pair;
+            diff.added_edges.push(EdgeDiff {
+                source,
+                target,
+                occurrence: *nth,
+                weight: edge.weight.clone(),
+            });
+        }
+        *nth += 1;
+    }
+
+    diff
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Apply the given diff to the `Dag`, identifying nodes by the key returned by `node_key`.
+    ///
+    /// The diff is applied atomically via a [`transaction`](Dag::transaction). If any of its
+    /// changes conflict with the current state of the `Dag` (e.g. a removed node's weight no
+    /// longer matches according to `PartialEq`, a modified or removed edge's weight no longer
+    /// matches according to `edge_eq`, or a removed edge no longer exists) or if applying it
+    /// would create a cycle, the `Dag` is left unchanged and a `PatchError` is returned.
+    ///
+    /// Modified and removed edges are located by their endpoints and their position among any
+    /// parallel edges, as in [`diff`]. Removing a node that still has edges not removed by the
+    /// diff is considered a conflict.
+    pub fn apply<K, F, G>(
+        &mut self,
+        diff: &DagDiff<K, N, E>,
+        mut node_key: F,
+        mut edge_eq: G,
+    ) -> Result<(), PatchError<K>>
+    where
+        N: Clone + PartialEq,
+        E: Clone,
+        K: Clone + Eq + Hash,
+        F: FnMut(&N) -> K,
+        G: FnMut(&E, &E) -> bool,
+    {
+        let mut keys = node_keys(self, &mut node_key);
+        let mut indices = key_indices(&keys);
+        let edges = edge_groups(self, &keys);
+        let result = self.transaction(|tx| {
+            for change in &diff.modified_nodes {
+                let n = find_node(&indices, &change.key)?;
+                if tx.dag()[n] != change.old {
+                    return Err(PatchError::NodeModified(change.key.clone()));
+                }
+                tx.set_node_weight(n, change.new.clone());
+            }
+            for change in &diff.modified_edges {
+                let (source, target) = (&change.source, &change.target);
+                let e = find_edge(&edges, &indices, source, target, change.occurrence)?;
+                if !edge_eq(&tx.dag()[e], &change.old) {
+                    return Err(PatchError::EdgeModified(source.clone(), target.clone()));
+                }
+                tx.set_edge_weight(e, change.new.clone());
+            }
+            let mut removed_edges = Vec::with_capacity(diff.removed_edges.len());
+            for removed in &diff.removed_edges {
+                let (source, target) = (&removed.source, &removed.target);
+                let e = find_edge(&edges, &indices, source, target, removed.occurrence)?;
+                if !edge_eq(&tx.dag()[e], &removed.weight) {
+                    return Err(PatchError::EdgeModified(source.clone(), target.clone()));
+                }
+                removed_edges.push(e);
+            }
+            // Removing an edge moves the last edge into its index, so remove from the last index
+            // down in order for the remaining indices to stay valid.
+            removed_edges.sort_unstable_by(|a, b| b.cmp(a));
+            removed_edges.dedup();
+            for e in removed_edges {
+                tx.remove_edge(e);
+            }
+            for (key, weight) in &diff.removed_nodes {
+                let n = find_node(&indices, key)?;
+                let dag = tx.dag();
+                if dag[n] != *weight {
+                    return Err(PatchError::NodeModified(key.clone()));
+                }
+                if dag.graph().neighbors_undirected(n).next().is_some() {
+                    return Err(PatchError::NodeHasEdges(key.clone()));
+                }
+                tx.remove_node(n);
+                indices.remove(key);
+                // The last node is moved into the removed node's index. Its key is taken from
+                // before the diff was applied, as its weight may have since been modified.
+                keys.swap_remove(n.index());
+                if let Some(moved) = keys.get(n.index()) {
+                    indices.insert(moved.clone(), n);
+                }
+            }
+            for (key, weight) in &diff.added_nodes {
+                if indices.contains_key(key) {
+                    return Err(PatchError::NodeExists(key.clone()));
+                }
+                let n = tx.add_node(weight.clone());
+                indices.insert(key.clone(), n);
+            }
+            for added in &diff.added_edges {
+                let a = find_node(&indices, &added.source)?;
+                let b = find_node(&indices, &added.target)?;
+                tx.add_edge(a, b, added.weight.clone());
+            }
+            Ok(())
+        });
+        result.map_err(|err| match err {
+            TransactionError::Aborted(err) => err,
+            TransactionError::WouldCycle => PatchError::WouldCycle,
+        })
+    }
+}
+
+fn find_node<K, Ix>(
+    indices: &HashMap<K, NodeIndex<Ix>>,
+    key: &K,
+) -> Result<NodeIndex<Ix>, PatchError<K>>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    indices
+        .get(key)
+        .copied()
+        .ok_or_else(|| PatchError::MissingNode(key.clone()))
+}
+
+fn find_edge<K, Ix>(
+    edges: &HashMap<(K, K), Vec<EdgeIndex<Ix>>>,
+    indices: &HashMap<K, NodeIndex<Ix>>,
+    source: &K,
+    target: &K,
+    occurrence: usize,
+) -> Result<EdgeIndex<Ix>, PatchError<K>>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    find_node(indices, source)?;
+    find_node(indices, target)?;
+    edges
+        .get(&(source.clone(), target.clone()))
+        .and_then(|es| es.get(occurrence))
+        .copied()
+        .ok_or_else(|| PatchError::MissingEdge(source.clone(), target.clone()))
+}
+
+// Match each of the old parallel edges with one of the new parallel edges, first those that are
+// equal and then the rest in order. Returns the new edge matched with each old edge, and whether
+// each new edge was matched.
+fn match_parallel<Ix, G>(
+    old: &[EdgeIndex<Ix>],
+    new: &[EdgeIndex<Ix>],
+    mut edge_eq: G,
+) -> (Vec<Option<usize>>, Vec<bool>)
+where
+    Ix: IndexType,
+    G: FnMut(EdgeIndex<Ix>, EdgeIndex<Ix>) -> bool,
+{
+    let mut matches = vec![None; old.len()];
+    let mut matched = vec![false; new.len()];
+    for (i, &a) in old.iter().enumerate() {
+        let equal = (0..new.len()).find(|&j| !matched[j] && edge_eq(a, new[j]));
+        if let Some(j) = equal {
+            matches[i] = Some(j);
+            matched[j] = true;
+        }
+    }
+    let mut rest = (0..new.len())
+        .filter(|&j| !matched[j])
+        .collect::<Vec<_>>()
+        .into_iter();
+    for m in matches.iter_mut().filter(|m| m.is_none()) {
+        *m = rest.next();
+        if let Some(j) = *m {
+            matched[j] = true;
+        }
+    }
+    (matches, matched)
+}
+
+fn node_keys<N, E, Ix, K, F>(dag: &Dag<N, E, Ix>, node_key: &mut F) -> Vec<K>
+where
+    Ix: IndexType,
+    F: FnMut(&N) -> K,
+{
+    dag.raw_nodes()
+        .iter()
+        .map(|n| node_key(&n.weight))
+        .collect()
+}
+
+fn key_indices<K, Ix>(keys: &[K]) -> HashMap<K, NodeIndex<Ix>>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    let mut indices = HashMap::with_capacity(keys.len());
+    for (i, key) in keys.iter().enumerate() {
+        let prev = indices.insert(key.clone(), NodeIndex::new(i));
+        assert!(prev.is_none(), "two nodes share the same key");
+    }
+    indices
+}
+
+fn endpoint_keys<K, Ix>(keys: &[K], a: NodeIndex<Ix>, b: NodeIndex<Ix>) -> (K, K)
+where
+    K: Clone,
+    Ix: IndexType,
+{
+    (keys[a.index()].clone(), keys[b.index()].clone())
+}
+
+// Group the indices of all edges by the keys of their endpoints, in order of edge index.
+fn edge_groups<N, E, Ix, K>(dag: &Dag<N, E, Ix>, keys: &[K]) -> HashMap<(K, K), Vec<EdgeIndex<Ix>>>
+where
+    Ix: IndexType,
+    K: Clone + Eq + Hash,
+{
+    let mut groups: HashMap<_, Vec<_>> = HashMap::new();
+    for (i, edge) in dag.raw_edges().iter().enumerate() {
+        let pair = endpoint_keys(keys, edge.source(), edge.target());
+        groups.entry(pair).or_default().push(EdgeIndex::new(i));
+    }
+    groups
+}
+
+impl<K> std::fmt::Display for PatchError<K>
+where
+    K: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PatchError::NodeExists(k) => write!(f, "node {:?} already exists", k),
+            PatchError::MissingNode(k) => write!(f, "node {:?} does not exist", k),
+            PatchError::NodeModified(k) => write!(f, "node {:?} has been modified", k),
+            PatchError::NodeHasEdges(k) => write!(f, "node {:?} still has edges", k),
+            PatchError::MissingEdge(a, b) => write!(f, "edge {:?} -> {:?} does not exist", a, b),
+            PatchError::EdgeModified(a, b) => {
+                write!(f, "edge {:?} -> {:?} has been modified", a, b)
+            }
+            PatchError::WouldCycle => write!(f, "applying the diff would create a cycle"),
+        }
+    }
+}
+
+impl<K> std::error::Error for PatchError<K> where K: std::fmt::Debug {}
@@ -0,0 +1,657 @@
+//! Reading and writing **Dag**s in the Graphviz DOT language.
+//!
+//! [`Dag::to_dot`] writes each node and edge with the attributes produced by the given closures
+//! and groups the nodes of each topological level into a `rank=same` subgraph, so that the
+//! layout reflects the depth of each node. [`Dag::from_dot`] parses a `digraph` into a
+//! `Dag<String, String>`, rejecting any input that contains a cycle.
+
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph::graph::{DefaultIx, IndexType};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The direction in which the ranks of a DOT graph are laid out, i.e. the `rankdir` attribute.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum RankDir {
+    /// Roots at the top, leaves at the bottom.
+    #[default]
+    TopToBottom,
+    /// Roots at the bottom, leaves at the top.
+    BottomToTop,
+    /// Roots on the left, leaves on the right.
+    LeftToRight,
+    /// Roots on the right, leaves on the left.
+    RightToLeft,
+}
+
+/// An error produced while parsing DOT via [`Dag::from_dot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FromDotError {
+    /// The input is not valid DOT, or uses a feature that is not supported.
+    Syntax {
+        /// The line on which the error occurred, starting from 1.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// The input describes an undirected `graph` rather than a `digraph`.
+    Undirected,
+    /// The edge between the nodes with the given DOT IDs would have created a cycle.
+    WouldCycle {
+        /// The ID of the edge's source node.
+        source: String,
+        /// The ID of the edge's target node.
+        target: String,
+    },
+}
+
+impl RankDir {
+    /// The value of the `rankdir` attribute.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RankDir::TopToBottom => "TB",
+            RankDir::BottomToTop => "BT",
+            RankDir::LeftToRight => "LR",
+            RankDir::RightToLeft => "RL",
+        }
+    }
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the `Dag` as a DOT `digraph`.
+    ///
+    /// Nodes are identified by their indices. The attributes of each node and edge are given as
+    /// `(name, value)` pairs by `node_attrs` and `edge_attrs` respectively. Values are always
+    /// quoted and escaped, so they may contain any text.
+    ///
+    /// Each topological level (the nodes whose longest path from a root has the same length)
+    /// containing more than one node is written as a `rank=same` subgraph.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    /// use daggy::dot::RankDir;
+    ///
+    /// let mut dag = Dag::<&str, u32>::new();
+    /// let root = dag.add_node("root");
+    /// dag.add_child(root, 1, "a");
+    /// dag.add_child(root, 2, "b");
+    ///
+    /// let dot = dag.to_dot(
+    ///     RankDir::LeftToRight,
+    ///     |_, n| vec![("label".into(), n.to_string())],
+    ///     |_, e| vec![("weight".into(), e.to_string())],
+    /// );
+    /// assert!(dot.contains("0 -> 2 [weight=\"2\"];"));
+    /// assert!(dot.contains("{ rank=same; 1; 2; }"));
+    /// ```
+    pub fn to_dot<F, G>(&self, rankdir: RankDir, mut node_attrs: F, mut edge_attrs: G) -> String
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> Vec<(String, String)>,
+        G: FnMut(EdgeIndex<Ix>, &E) -> Vec<(String, String)>,
+    {
+        let mut out = String::new();
+        out.push_str("digraph {\n");
+        writeln!(out, "    rankdir={};", rankdir.as_str()).unwrap();
+        for (i, node) in self.graph.raw_nodes().iter().enumerate() {
+            write!(out, "    {}", i).unwrap();
+            write_attrs(&mut out, &node_attrs(NodeIndex::new(i), &node.weight));
+            out.push_str(";\n");
+        }
+        for (i, edge) in self.graph.raw_edges().iter().enumerate() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            write!(out, "    {} -> {}", a, b).unwrap();
+            write_attrs(&mut out, &edge_attrs(EdgeIndex::new(i), &edge.weight));
+            out.push_str(";\n");
+        }
+        let depths = self.fold_down(|_, _| 0, |depth, _, &parent| depth.max(parent + 1));
+        let mut levels: Vec<Vec<usize>> = vec![];
+        for (i, depth) in depths.into_iter().enumerate() {
+            if levels.len() <= depth {
+                levels.resize(depth + 1, vec![]);
+            }
+            levels[depth].push(i);
+        }
+        for level in levels.iter().filter(|level| level.len() > 1) {
+            out.push_str("    { rank=same;");
+            for i in level {
+                write!(out, " {};", i).unwrap();
+            }
+            out.push_str(" }\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Dag<String, String, DefaultIx> {
+    /// Parse a DOT `digraph` into a `Dag`.
+    ///
+    /// Nodes are added in the order in which their IDs first appear. The weight of each node is
+    /// its `label` attribute if it has one, or its ID otherwise. The weight of each edge is its
+    /// `label` attribute, or an empty string if it has none. All other attributes, along with
+    /// ports and graph, node and edge default attributes, are ignored. Edges to or from a
+    /// subgraph connect every node within it. Within a `strict` digraph, repeated edges are merged.
+    ///
+    /// Returns an error if the input is not a valid `digraph`, if its subgraphs are nested more
+    /// than 256 deep or if any of its edges would create a cycle.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let dag = Dag::from_dot("digraph { a -> b -> c; a [label=\"A\"]; }").unwrap();
+    /// assert_eq!(dag.node_count(), 3);
+    /// assert_eq!(dag.edge_count(), 2);
+    /// assert_eq!(dag.raw_nodes()[0].weight, "A");
+    /// ```
+    pub fn from_dot(dot: &str) -> Result<Self, FromDotError> {
+        let tokens = lex(dot)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            depth: 0,
+            strict: false,
+            dag: Dag::new(),
+            nodes: HashMap::new(),
+        };
+        parser.graph()?;
+        Ok(parser.dag)
+    }
+}
+
+// Write the attribute list, if any, with a leading space.
+fn write_attrs(out: &mut String, attrs: &[(String, String)]) {
+    if attrs.is_empty() {
+        return;
+    }
+    out.push_str(" [");
+    for (i, (name, value)) in attrs.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        if is_plain_id(name) {
+            out.push_str(name);
+        } else {
+            write_quoted(out, name);
+        }
+        out.push('=');
+        write_quoted(out, value);
+    }
+    out.push(']');
+}
+
+fn is_plain_id(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_') && keyword(s).is_none()
+}
+
+fn write_quoted(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Keyword {
+    Strict,
+    Graph,
+    Digraph,
+    Node,
+    Edge,
+    Subgraph,
+}
+
+fn keyword(s: &str) -> Option<Keyword> {
+    let keyword = match s.to_ascii_lowercase().as_str() {
+        "strict" => Keyword::Strict,
+        "graph" => Keyword::Graph,
+        "digraph" => Keyword::Digraph,
+        "node" => Keyword::Node,
+        "edge" => Keyword::Edge,
+        "subgraph" => Keyword::Subgraph,
+        _ => return None,
+    };
+    Some(keyword)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    // An identifier, numeral, quoted string or HTML string.
+    Id(String),
+    // An unquoted identifier matching one of the DOT keywords.
+    Keyword(Keyword),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Semi,
+    Comma,
+    Eq,
+    Colon,
+    Plus,
+    Arrow,
+    UndirectedEdge,
+}
+
+fn syntax<T>(line: usize, message: impl Into<String>) -> Result<T, FromDotError> {
+    Err(FromDotError::Syntax {
+        line,
+        message: message.into(),
+    })
+}
+
+// Split the input into tokens, each paired with the line on which it begins.
+fn lex(input: &str) -> Result<Vec<(Token, usize)>, FromDotError> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+    let mut line_start = true;
+    while let Some(c) = chars.next() {
+        let start = line;
+        let token = match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            // Lines beginning with `#` are treated as C preprocessor output and discarded.
+            '#' if line_start => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+                continue;
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = None;
+                loop {
+                    match chars.next() {
+                        Some('/') if prev == Some('*') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            prev = Some(c);
+                        }
+                        None => return syntax(start, "unterminated comment"),
+                    }
+                }
+                continue;
+            }
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ';' => Token::Semi,
+            ',' => Token::Comma,
+            '=' => Token::Eq,
+            ':' => Token::Colon,
+            '+' => Token::Plus,
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                Token::Arrow
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                Token::UndirectedEdge
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => s.push('"'),
+                            Some('\\') => s.push('\\'),
+                            Some('n') | Some('l') | Some('r') => s.push('\n'),
+                            // A backslash before a newline continues the string.
+                            Some('\n') => line += 1,
+                            Some(c) => {
+                                s.push('\\');
+                                s.push(c);
+                            }
+                            None => return syntax(start, "unterminated string"),
+                        },
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            s.push(c);
+                        }
+                        None => return syntax(start, "unterminated string"),
+                    }
+                }
+                Token::Id(s)
+            }
+            '<' => {
+                let mut s = String::new();
+                let mut depth = 1;
+                loop {
+                    let c = match chars.next() {
+                        Some(c) => c,
+                        None => return syntax(start, "unterminated HTML string"),
+                    };
+                    match c {
+                        '<' => depth += 1,
+                        '>' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        '\n' => line += 1,
+                        _ => (),
+                    }
+                    s.push(c);
+                }
+                Token::Id(s)
+            }
+            c if c == '-' || c == '.' || c.is_ascii_digit() => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c.is_ascii_digit() {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if !s.chars().any(|c| c.is_ascii_digit()) || s[1..].contains('-') {
+                    return syntax(start, format!("invalid numeral `{}`", s));
+                }
+                Token::Id(s)
+            }
+            c if c.is_alphabetic() || c == '_' || !c.is_ascii() => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || !c.is_ascii() {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                match keyword(&s) {
+                    Some(keyword) => Token::Keyword(keyword),
+                    None => Token::Id(s),
+                }
+            }
+            c => return syntax(start, format!("unexpected character `{}`", c)),
+        };
+        line_start = false;
+        tokens.push((token, start));
+    }
+    Ok(tokens)
+}
+
+// The maximum nesting depth of subgraphs, beyond which the parser would risk overflowing the stack.
+const MAX_DEPTH: usize = 256;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    // The number of subgraphs enclosing the current statement.
+    depth: usize,
+    strict: bool,
+    dag: Dag<String, String>,
+    nodes: HashMap<String, NodeIndex>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some(&(_, line)) => line,
+            None => 1,
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), FromDotError> {
+        if self.eat(&token) {
+            Ok(())
+        } else {
+            syntax(self.line(), format!("expected {}", what))
+        }
+    }
+
+    // An ID, concatenating any quoted strings joined by `+`.
+    fn id(&mut self) -> Result<String, FromDotError> {
+        let mut id = match self.peek() {
+            Some(Token::Id(id)) => id.clone(),
+            _ => return syntax(self.line(), "expected an ID"),
+        };
+        self.pos += 1;
+        while self.peek() == Some(&Token::Plus) {
+            self.pos += 1;
+            match self.peek() {
+                Some(Token::Id(s)) => id.push_str(s),
+                _ => return syntax(self.line(), "expected a string after `+`"),
+            }
+            self.pos += 1;
+        }
+        Ok(id)
+    }
+
+    fn graph(&mut self) -> Result<(), FromDotError> {
+        self.strict = self.eat(&Token::Keyword(Keyword::Strict));
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Digraph)) => self.pos += 1,
+            Some(Token::Keyword(Keyword::Graph)) => return Err(FromDotError::Undirected),
+            _ => return syntax(self.line(), "expected `digraph`"),
+        }
+        if let Some(Token::Id(_)) = self.peek() {
+            self.id()?;
+        }
+        self.expect(Token::LBrace, "`{`")?;
+        self.stmts(&mut vec![])?;
+        if self.pos < self.tokens.len() {
+            return syntax(self.line(), "unexpected input after the graph");
+        }
+        Ok(())
+    }
+
+    // Parse statements up to and including the closing brace, collecting the mentioned nodes.
+    fn stmts(&mut self, nodes: &mut Vec<NodeIndex>) -> Result<(), FromDotError> {
+        while !self.eat(&Token::RBrace) {
+            if self.peek().is_none() {
+                return syntax(self.line(), "expected `}`");
+            }
+            self.stmt(nodes)?;
+            self.eat(&Token::Semi);
+        }
+        Ok(())
+    }
+
+    fn stmt(&mut self, nodes: &mut Vec<NodeIndex>) -> Result<(), FromDotError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Graph))
+            | Some(Token::Keyword(Keyword::Node))
+            | Some(Token::Keyword(Keyword::Edge)) => {
+                self.pos += 1;
+                if self.peek() != Some(&Token::LBracket) {
+                    return syntax(self.line(), "expected `[`");
+                }
+                self.attrs()?;
+                return Ok(());
+            }
+            Some(Token::Id(_))
+                if self.tokens.get(self.pos + 1).map(|(t, _)| t) == Some(&Token::Eq) =>
+            {
+                self.pos += 2;
+                self.id()?;
+                return Ok(());
+            }
+            _ => (),
+        }
+        let (mut sources, id) = self.endpoint()?;
+        nodes.extend(&sources);
+        if self.peek() != Some(&Token::Arrow) {
+            if self.peek() == Some(&Token::UndirectedEdge) {
+                return syntax(self.line(), "undirected edge `--` in a digraph");
+            }
+            let attrs = self.attrs()?;
+            if let (Some(node), Some(label)) = (id, label(attrs)) {
+                self.dag[node] = label;
+            }
+            return Ok(());
+        }
+        let mut edges = vec![];
+        while self.eat(&Token::Arrow) {
+            let (targets, _) = self.endpoint()?;
+            nodes.extend(&targets);
+            edges.push((sources, targets.clone()));
+            sources = targets;
+        }
+        let weight = label(self.attrs()?);
+        for (sources, targets) in edges {
+            for &a in &sources {
+                for &b in &targets {
+                    self.add_edge(a, b, weight.as_ref())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // A node ID with an optional port, or a subgraph. Also returns the node for a node ID.
+    fn endpoint(&mut self) -> Result<(Vec<NodeIndex>, Option<NodeIndex>), FromDotError> {
+        match self.peek() {
+            Some(Token::Keyword(Keyword::Subgraph)) | Some(Token::LBrace) => {
+                if self.eat(&Token::Keyword(Keyword::Subgraph)) {
+                    if let Some(Token::Id(_)) = self.peek() {
+                        self.id()?;
+                    }
+                }
+                self.expect(Token::LBrace, "`{`")?;
+                if self.depth == MAX_DEPTH {
+                    return syntax(self.line(), "subgraphs are nested too deeply");
+                }
+                let mut nodes = vec![];
+                self.depth += 1;
+                self.stmts(&mut nodes)?;
+                self.depth -= 1;
+                Ok((nodes, None))
+            }
+            _ => {
+                let id = self.id()?;
+                // Ports and compass points are not represented in the `Dag`.
+                while self.eat(&Token::Colon) {
+                    self.id()?;
+                }
+                let node = self.node(id);
+                Ok((vec![node], Some(node)))
+            }
+        }
+    }
+
+    // Zero or more bracketed attribute lists.
+    fn attrs(&mut self) -> Result<Vec<(String, String)>, FromDotError> {
+        let mut attrs = vec![];
+        while self.eat(&Token::LBracket) {
+            while !self.eat(&Token::RBracket) {
+                let name = self.id()?;
+                self.expect(Token::Eq, "`=`")?;
+                let value = self.id()?;
+                attrs.push((name, value));
+                if !self.eat(&Token::Comma) {
+                    self.eat(&Token::Semi);
+                }
+            }
+        }
+        Ok(attrs)
+    }
+
+    fn node(&mut self, id: String) -> NodeIndex {
+        if let Some(&node) = self.nodes.get(&id) {
+            return node;
+        }
+        let node = self.dag.add_node(id.clone());
+        self.nodes.insert(id, node);
+        node
+    }
+
+    // Add an edge, or in a `strict` graph update the label of any existing edge.
+    fn add_edge(
+        &mut self,
+        a: NodeIndex,
+        b: NodeIndex,
+        label: Option<&String>,
+    ) -> Result<(), FromDotError> {
+        if self.strict {
+            if let Some(edge) = self.dag.find_edge(a, b) {
+                if let Some(label) = label {
+                    self.dag[edge] = label.clone();
+                }
+                return Ok(());
+            }
+        }
+        let weight = label.cloned().unwrap_or_default();
+        if self.dag.add_edge(a, b, weight).is_err() {
+            let id = |n: NodeIndex| {
+                let (id, _) = self.nodes.iter().find(|&(_, &i)| i == n).unwrap();
+                id.clone()
+            };
+            return Err(FromDotError::WouldCycle {
+                source: id(a),
+                target: id(b),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn label(attrs: Vec<(String, String)>) -> Option<String> {
+    attrs
+        .into_iter()
+        .rev()
+        .find(|(name, _)| name == "label")
+        .map(|(_, value)| value)
+}
+
+impl std::fmt::Display for FromDotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FromDotError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            FromDotError::Undirected => {
+                write!(f, "expected a digraph but found an undirected graph")
+            }
+            FromDotError::WouldCycle { source, target } => {
+                write!(f, "edge {:?} -> {:?} would create a cycle", source, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FromDotError {}
@@ -0,0 +1,436 @@
+//! Streaming import and export of **Dag**s as delimited edge lists, e.g. CSV or TSV.
+//!
+//! Each record describes a single edge as `parent,child` or `parent,child,label`, where nodes are
+//! identified by name. Fields may be quoted with `"`, in which case a `""` within the field stands
+//! for a single quote, and unquoted fields have any surrounding spaces trimmed. Records may not
+//! span multiple lines.
+
+use crate::{Dag, NodeIndex};
+use petgraph::graph::{DefaultIx, IndexType};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+/// How [`Dag::read_edge_list`] handles a record whose parent and child are already joined by an
+/// earlier record.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Duplicates {
+    /// Add a parallel edge for every record.
+    #[default]
+    Keep,
+    /// Keep the first edge and ignore any later records.
+    Skip,
+    /// Keep the first edge, replacing its label with that of the last record.
+    Replace,
+    /// Fail with [`EdgeListError::DuplicateEdge`].
+    Error,
+}
+
+/// Options controlling the format read by [`Dag::read_edge_list`] and written by
+/// [`Dag::write_edge_list`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EdgeListOptions {
+    delimiter: char,
+    header: bool,
+    comment: Option<char>,
+    duplicates: Duplicates,
+}
+
+/// An error produced while reading an edge list via [`Dag::read_edge_list`].
+#[derive(Debug)]
+pub enum EdgeListError {
+    /// An error occurred while reading from the underlying reader.
+    Io(io::Error),
+    /// A record could not be parsed.
+    Syntax {
+        /// The line on which the error occurred, starting from 1.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// The record joins two nodes that are already joined and the policy is
+    /// [`Duplicates::Error`].
+    DuplicateEdge {
+        /// The line of the duplicate record, starting from 1.
+        line: usize,
+        /// The name of the edge's parent node.
+        source: String,
+        /// The name of the edge's child node.
+        target: String,
+    },
+    /// The edge described by the record would have created a cycle.
+    WouldCycle {
+        /// The line of the record that closes the cycle, starting from 1.
+        line: usize,
+        /// The name of the edge's parent node.
+        source: String,
+        /// The name of the edge's child node.
+        target: String,
+    },
+}
+
+impl EdgeListOptions {
+    /// Comma separated values.
+    ///
+    /// By default there is no header row, lines beginning with `#` are skipped and duplicate
+    /// edges are kept.
+    pub fn csv() -> Self {
+        EdgeListOptions {
+            delimiter: ',',
+            header: false,
+            comment: Some('#'),
+            duplicates: Duplicates::Keep,
+        }
+    }
+
+    /// Tab separated values, otherwise as [`EdgeListOptions::csv`].
+    pub fn tsv() -> Self {
+        Self::csv().with_delimiter('\t')
+    }
+
+    /// Separate fields with the given character.
+    ///
+    /// **Panics** if `delimiter` is `"` or a line break.
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        assert!(
+            !matches!(delimiter, '"' | '\n' | '\r'),
+            "invalid delimiter {:?}",
+            delimiter
+        );
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Whether the first record is a header row, which is skipped when reading and written as
+    /// `parent,child,label` when writing.
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Skip lines whose first non-blank character is the given character, or disable comments
+    /// with `None`.
+    pub fn with_comment(mut self, comment: Option<char>) -> Self {
+        self.comment = comment;
+        self
+    }
+
+    /// Choose how records that duplicate an earlier edge are handled when reading.
+    pub fn with_duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+}
+
+impl Default for EdgeListOptions {
+    fn default() -> Self {
+        Self::csv()
+    }
+}
+
+impl Dag<String, String, DefaultIx> {
+    /// Read a `Dag` from an edge list, one record per line.
+    ///
+    /// Each node's weight is its name and each edge's weight is its label, or an empty string if
+    /// the record has only two fields. Nodes are added in the order in which their names first
+    /// appear, and edges in the order of their records. Blank lines are skipped.
+    ///
+    /// The input is read a line at a time and all edges are added in a single batch via
+    /// [`Dag::add_edges`], so that the cycle check is only performed once. If the edges contain a
+    /// cycle, the error reports the first record that closes it.
+    ///
+    /// ```rust
+    /// use daggy::edge_list::EdgeListOptions;
+    /// use daggy::Dag;
+    ///
+    /// let csv = "parent,child,label\nfetch,build,src\nbuild,test,\"bin, lib\"\n";
+    /// let options = EdgeListOptions::csv().with_header(true);
+    /// let dag = Dag::read_edge_list(csv.as_bytes(), &options).unwrap();
+    /// assert_eq!(dag.node_count(), 3);
+    /// assert_eq!(dag.raw_nodes()[2].weight, "test");
+    /// assert_eq!(dag.raw_edges()[1].weight, "bin, lib");
+    /// ```
+    pub fn read_edge_list<R>(reader: R, options: &EdgeListOptions) -> Result<Self, EdgeListError>
+    where
+        R: BufRead,
+    {
+        let mut dag = Dag::new();
+        let mut nodes: HashMap<String, NodeIndex> = HashMap::new();
+        let mut edges: Vec<(NodeIndex, NodeIndex, String)> = vec![];
+        let mut lines: Vec<usize> = vec![];
+        let mut existing: HashMap<(NodeIndex, NodeIndex), usize> = HashMap::new();
+        let mut header = options.header;
+
+        for (i, line) in reader.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line?;
+            let line = match i {
+                0 => line.trim_start_matches('\u{feff}'),
+                _ => &line[..],
+            };
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || options.comment.is_some_and(|c| trimmed.starts_with(c)) {
+                continue;
+            }
+            if header {
+                header = false;
+                continue;
+            }
+
+            let fields =
+                split_record(line, options.delimiter).map_err(|message| EdgeListError::Syntax {
+                    line: line_number,
+                    message: message.to_string(),
+                })?;
+            let (parent, child, label) = match fields.len() {
+                2 | 3 => {
+                    let mut fields = fields.into_iter();
+                    let parent = fields.next().unwrap();
+                    let child = fields.next().unwrap();
+                    (parent, child, fields.next().unwrap_or_default())
+                }
+                n => {
+                    return Err(EdgeListError::Syntax {
+                        line: line_number,
+                        message: format!("expected 2 or 3 fields, found {}", n),
+                    })
+                }
+            };
+            if parent.is_empty() || child.is_empty() {
+                return Err(EdgeListError::Syntax {
+                    line: line_number,
+                    message: "node names must not be empty".to_string(),
+                });
+            }
+
+            let mut node = |name: String| match nodes.get(&name) {
+                Some(&node) => node,
+                None => {
+                    let node = dag.add_node(name.clone());
+                    nodes.insert(name, node);
+                    node
+                }
+            };
+            let (a, b) = (node(parent), node(child));
+            if options.duplicates != Duplicates::Keep {
+                if let Some(&e) = existing.get(&(a, b)) {
+                    match options.duplicates {
+                        Duplicates::Keep | Duplicates::Skip => (),
+                        Duplicates::Replace => edges[e].2 = label,
+                        Duplicates::Error => {
+                            return Err(EdgeListError::DuplicateEdge {
+                                line: line_number,
+                                source: dag[a].clone(),
+                                target: dag[b].clone(),
+                            })
+                        }
+                    }
+                    continue;
+                }
+                existing.insert((a, b), edges.len());
+            }
+            edges.push((a, b, label));
+            lines.push(line_number);
+        }
+
+        let endpoints: Vec<_> = edges.iter().map(|&(a, b, _)| (a, b)).collect();
+        if let Err(err) = dag.add_edges(edges) {
+            // Re-add the edges one at a time to find the record that closes the cycle.
+            let weights = err.0.into_iter().rev();
+            for ((&(a, b), line), weight) in endpoints.iter().zip(lines).zip(weights) {
+                if dag.add_edge(a, b, weight).is_err() {
+                    return Err(EdgeListError::WouldCycle {
+                        line,
+                        source: dag[a].clone(),
+                        target: dag[b].clone(),
+                    });
+                }
+            }
+            unreachable!("`add_edges` reported a cycle that `add_edge` did not");
+        }
+        Ok(dag)
+    }
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write every edge of the `Dag` as a record of its parent's name, child's name and label,
+    /// where the names and labels are produced via each weight's `Display` implementation.
+    ///
+    /// Fields are quoted where necessary so that they are read back unchanged by
+    /// [`Dag::read_edge_list`]. Nodes without any edges are not written, and the round trip
+    /// only preserves the graph if every node's name is unique.
+    ///
+    /// Returns an error of kind `InvalidData` if a name or label contains a line break, or if a
+    /// name is empty, as neither could be read back.
+    ///
+    /// ```rust
+    /// use daggy::edge_list::EdgeListOptions;
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<&str, &str>::new();
+    /// let a = dag.add_node("a");
+    /// dag.add_child(a, "x, y", "b");
+    ///
+    /// let mut csv = vec![];
+    /// dag.write_edge_list(&mut csv, &EdgeListOptions::csv()).unwrap();
+    /// assert_eq!(String::from_utf8(csv).unwrap(), "a,b,\"x, y\"\n");
+    /// ```
+    pub fn write_edge_list<W>(&self, mut writer: W, options: &EdgeListOptions) -> io::Result<()>
+    where
+        W: Write,
+        N: Display,
+        E: Display,
+    {
+        let mut record = String::new();
+        if options.header {
+            write_record(
+                &mut writer,
+                &mut record,
+                options,
+                ["parent", "child", "label"],
+            )?;
+        }
+        for edge in self.graph.raw_edges() {
+            let parent = self.graph[edge.source()].to_string();
+            let child = self.graph[edge.target()].to_string();
+            if parent.is_empty() || child.is_empty() {
+                let msg = "node names must not be empty";
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+            let label = edge.weight.to_string();
+            write_record(&mut writer, &mut record, options, [&parent, &child, &label])?;
+        }
+        writer.flush()
+    }
+}
+
+// Split a line into its fields, unquoting quoted fields and trimming unquoted ones.
+fn split_record(line: &str, delimiter: char) -> Result<Vec<String>, &'static str> {
+    let mut fields = vec![];
+    let mut rest = line;
+    loop {
+        let start = rest.trim_start_matches(' ');
+        if let Some(quoted) = start.strip_prefix('"') {
+            let mut field = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next() {
+                    None => return Err("unterminated quoted field"),
+                    Some((i, '"')) => {
+                        if quoted[i + 1..].starts_with('"') {
+                            field.push('"');
+                            chars.next();
+                        } else {
+                            break i + 1;
+                        }
+                    }
+                    Some((_, c)) => field.push(c),
+                }
+            };
+            fields.push(field);
+            rest = quoted[end..].trim_start_matches(' ');
+            match rest.strip_prefix(delimiter) {
+                Some(after) => rest = after,
+                None if rest.is_empty() => return Ok(fields),
+                None => return Err("unexpected text after quoted field"),
+            }
+        } else {
+            match rest.find(delimiter) {
+                Some(end) => {
+                    fields.push(rest[..end].trim_matches(' ').to_string());
+                    rest = &rest[end + delimiter.len_utf8()..];
+                }
+                None => {
+                    fields.push(rest.trim_matches(' ').to_string());
+                    return Ok(fields);
+                }
+            }
+        }
+    }
+}
+
+// Write a single record, quoting any field that would not otherwise be read back unchanged.
+fn write_record<W, S>(
+    writer: &mut W,
+    record: &mut String,
+    options: &EdgeListOptions,
+    fields: [S; 3],
+) -> io::Result<()>
+where
+    W: Write,
+    S: AsRef<str>,
+{
+    record.clear();
+    for (i, field) in fields.iter().enumerate() {
+        let field = field.as_ref();
+        if field.contains(['\n', '\r']) {
+            let msg = format!("field {:?} contains a line break", field);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+        if i > 0 {
+            record.push(options.delimiter);
+        }
+        let needs_quotes = field.contains([options.delimiter, '"'])
+            || field.starts_with(' ')
+            || field.ends_with(' ')
+            || (i == 0 && field.starts_with('\u{feff}'))
+            || (i == 0
+                && options
+                    .comment
+                    .is_some_and(|c| field.trim_start().starts_with(c)));
+        if needs_quotes {
+            record.push('"');
+            record.push_str(&field.replace('"', "\"\""));
+            record.push('"');
+        } else {
+            record.push_str(field);
+        }
+    }
+    record.push('\n');
+    writer.write_all(record.as_bytes())
+}
+
+impl From<io::Error> for EdgeListError {
+    fn from(err: io::Error) -> Self {
+        EdgeListError::Io(err)
+    }
+}
+
+impl std::fmt::Display for EdgeListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EdgeListError::Io(err) => write!(f, "failed to read edge list: {}", err),
+            EdgeListError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            EdgeListError::DuplicateEdge {
+                line,
+                source,
+                target,
+            } => write!(
+                f,
+                "line {}: duplicate edge {:?} -> {:?}",
+                line, source, target
+            ),
+            EdgeListError::WouldCycle {
+                line,
+                source,
+                target,
+            } => write!(
+                f,
+                "line {}: edge {:?} -> {:?} would create a cycle",
+                line, source, target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EdgeListError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EdgeListError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
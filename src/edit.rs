@@ -0,0 +1,224 @@
+//! Invertible edits to a **Dag**, used to implement rollback and undo.
+//!
+//! Applying an **Edit** returns the **Edit** that reverses it, including the positions of all node
+//! and edge indices that were shifted by a removal. Re-inserting a removed edge places it at the
+//! head of its endpoints' adjacency lists, so after reversing a removal the original order of
+//! neighbours is restored via [`Dag::restore_adjacency`] using the ages recorded by
+//! [`Dag::edge_ages`] before the removal.
+
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph as pg;
+use petgraph::graph::{Graph, IndexType};
+use petgraph::visit::EdgeRef;
+
+/// An edge that was removed from a **Dag**, along with the index it occupied.
+#[derive(Clone, Debug)]
+pub(crate) struct RemovedEdge<E, Ix> {
+    pub index: EdgeIndex<Ix>,
+    pub source: NodeIndex<Ix>,
+    pub target: NodeIndex<Ix>,
+    pub weight: E,
+}
+
+/// A single invertible edit to the structure or weights of a **Dag**.
+#[derive(Clone, Debug)]
+pub(crate) enum Edit<N, E, Ix> {
+    /// Insert a node at the given index, along with the edges that were removed with it.
+    InsertNode {
+        index: NodeIndex<Ix>,
+        weight: N,
+        edges: Vec<RemovedEdge<E, Ix>>,
+    },
+    /// Remove the node at the given index, along with all of its edges.
+    RemoveNode(NodeIndex<Ix>),
+    /// Insert an edge at the given index.
+    InsertEdge(RemovedEdge<E, Ix>),
+    /// Remove the edge at the given index.
+    RemoveEdge(EdgeIndex<Ix>),
+    /// Replace the weight of the node at the given index.
+    NodeWeight(NodeIndex<Ix>, N),
+    /// Replace the weight of the edge at the given index.
+    EdgeWeight(EdgeIndex<Ix>, E),
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Apply the given edit without checking for cycles and return the edit that reverses it.
+    ///
+    /// **Panics** if the edit refers to an element that does not exist.
+    pub(crate) fn apply_edit(&mut self, edit: Edit<N, E, Ix>) -> Edit<N, E, Ix> {
+        match edit {
+            Edit::InsertNode {
+                index,
+                weight,
+                edges,
+            } => {
+                self.insert_node_at(index, weight);
+                for edge in edges.into_iter().rev() {
+                    self.add_edge_at(edge.index, edge.source, edge.target, edge.weight);
+                }
+                Edit::RemoveNode(index)
+            }
+            Edit::RemoveNode(index) => {
+                let edges = self.detach_edges(index);
+                let weight = self.graph.remove_node(index).expect("no node for index");
+                Edit::InsertNode {
+                    index,
+                    weight,
+                    edges,
+                }
+            }
+            Edit::InsertEdge(edge) => {
+                self.add_edge_at(edge.index, edge.source, edge.target, edge.weight);
+                Edit::RemoveEdge(edge.index)
+            }
+            Edit::RemoveEdge(index) => {
+                let (source, target) = self.edge_endpoints(index).expect("no edge for index");
+                let weight = self.graph.remove_edge(index).expect("no edge for index");
+                Edit::InsertEdge(RemovedEdge {
+                    index,
+                    source,
+                    target,
+                    weight,
+                })
+            }
+            Edit::NodeWeight(index, weight) => {
+                let old = std::mem::replace(&mut self.graph[index], weight);
+                Edit::NodeWeight(index, old)
+            }
+            Edit::EdgeWeight(index, weight) => {
+                let old = std::mem::replace(&mut self.graph[index], weight);
+                Edit::EdgeWeight(index, old)
+            }
+        }
+    }
+
+    /// Remove all edges connected to the given node in the same order as `Graph::remove_node`.
+    fn detach_edges(&mut self, node: NodeIndex<Ix>) -> Vec<RemovedEdge<E, Ix>> {
+        let mut removed = vec![];
+        for &dir in &[pg::Outgoing, pg::Incoming] {
+            while let Some(index) = self.graph.first_edge(node, dir) {
+                let (source, target) = self.graph.edge_endpoints(index).expect("no edge");
+                let weight = self.graph.remove_edge(index).expect("no edge");
+                removed.push(RemovedEdge {
+                    index,
+                    source,
+                    target,
+                    weight,
+                });
+            }
+        }
+        removed
+    }
+
+    /// Add a node and move it to the given index, moving the node currently at that index (along
+    /// with its edges) back to the end of the node list.
+    ///
+    /// This exactly reverses the swap performed by `remove_node` for an edgeless node.
+    fn insert_node_at(&mut self, index: NodeIndex<Ix>, weight: N) {
+        let last = self.graph.add_node(weight);
+        if index == last {
+            return;
+        }
+        let detached = self.detach_edges(index);
+        // Removing the displaced node swaps the newly added node into its place.
+        let displaced = self.graph.remove_node(index).expect("index out of bounds");
+        let moved = self.graph.add_node(displaced);
+        for edge in detached.into_iter().rev() {
+            let remap = |n| if n == index { moved } else { n };
+            let (source, target) = (remap(edge.source), remap(edge.target));
+            self.add_edge_at(edge.index, source, target, edge.weight);
+        }
+    }
+
+    /// The relative age of each edge by index, where older edges have smaller ages.
+    ///
+    /// Every adjacency list is ordered from the most to the least recently added edge, so the
+    /// ages are found by a topological sort of the "added before" relation between neighbouring
+    /// entries of each list.
+    pub(crate) fn edge_ages(&self) -> Vec<usize> {
+        let edge_count = self.graph.edge_count();
+        // The number of older edges that must be aged before each edge.
+        let mut pending = vec![0u8; edge_count];
+        // The newer neighbour of each edge within its outgoing and incoming lists.
+        let mut newer = vec![[None; 2]; edge_count];
+        for node in self.graph.node_indices() {
+            for (slot, &dir) in [pg::Outgoing, pg::Incoming].iter().enumerate() {
+                let list: Vec<usize> = self
+                    .graph
+                    .edges_directed(node, dir)
+                    .map(|e| e.id().index())
+                    .collect();
+                for pair in list.windows(2) {
+                    pending[pair[0]] += 1;
+                    newer[pair[1]][slot] = Some(pair[0]);
+                }
+            }
+        }
+        let mut ready: Vec<usize> = (0..edge_count).filter(|&e| pending[e] == 0).collect();
+        let mut ages = vec![0; edge_count];
+        let mut age = 0;
+        while let Some(e) = ready.pop() {
+            ages[e] = age;
+            age += 1;
+            for &n in newer[e].iter().flatten() {
+                pending[n] -= 1;
+                if pending[n] == 0 {
+                    ready.push(n);
+                }
+            }
+        }
+        ages
+    }
+
+    /// Rebuild the adjacency lists so that each is ordered by the given edge ages, as though the
+    /// edges had been added from oldest to newest, without changing any node or edge index.
+    ///
+    /// `ages` may contain more entries than there are edges, in which case the extra entries are
+    /// ignored.
+    pub(crate) fn restore_adjacency(&mut self, ages: &[usize]) {
+        let (node_count, edge_count) = (self.graph.node_count(), self.graph.edge_count());
+        // The rebuild temporarily requires a placeholder for every edge.
+        if edge_count == 0 || edge_count >= <Ix as IndexType>::max().index() / 2 {
+            return;
+        }
+        let mut order: Vec<usize> = (0..edge_count).collect();
+        order.sort_by_key(|&e| ages[e]);
+
+        // Add a placeholder for every index, followed by the edges from oldest to newest.
+        let mut skeleton =
+            Graph::<(), Option<usize>, pg::Directed, Ix>::with_capacity(node_count, edge_count * 2);
+        for _ in 0..node_count {
+            skeleton.add_node(());
+        }
+        let first = NodeIndex::new(0);
+        for _ in 0..edge_count {
+            skeleton.add_edge(first, first, None);
+        }
+        for &e in &order {
+            let (a, b) = self
+                .graph
+                .edge_endpoints(EdgeIndex::new(e))
+                .expect("no edge");
+            skeleton.add_edge(a, b, Some(e));
+        }
+        // Removing an edge moves the last edge into its index without changing the position of
+        // the moved edge within any adjacency list, so replace each placeholder with its edge.
+        while skeleton.edge_count() > edge_count {
+            let last = EdgeIndex::new(skeleton.edge_count() - 1);
+            let e = skeleton[last].expect("placeholder was moved");
+            skeleton.remove_edge(EdgeIndex::new(e));
+        }
+
+        let graph = std::mem::take(&mut self.graph);
+        let (nodes, edges) = graph.into_nodes_edges();
+        let mut nodes: Vec<_> = nodes.into_iter().map(|n| Some(n.weight)).collect();
+        let mut edges: Vec<_> = edges.into_iter().map(|e| Some(e.weight)).collect();
+        self.graph = skeleton.map(
+            |n, _| nodes[n.index()].take().expect("no node"),
+            |_, &e| edges[e.expect("placeholder")].take().expect("no edge"),
+        );
+    }
+}
@@ -0,0 +1,725 @@
+//! Reading and writing **Dag**s in the GraphML format, as used by tools such as yEd and Gephi.
+//!
+//! Node and edge weights are mapped to and from sets of typed GraphML attributes by user-provided
+//! closures. The attribute keys are declared from the names and types of the written values.
+//!
+//! Only available with the `graphml` feature enabled.
+
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph::graph::{DefaultIx, IndexType};
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+/// The GraphML attributes of a single node or edge, by name.
+pub type Attributes = BTreeMap<String, AttrValue>;
+
+/// The type of a GraphML attribute, i.e. the `attr.type` of its key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AttrType {
+    /// `boolean`
+    Boolean,
+    /// `int`, a 32-bit integer.
+    Int,
+    /// `long`, a 64-bit integer.
+    Long,
+    /// `float`, a 32-bit floating point number.
+    Float,
+    /// `double`, a 64-bit floating point number.
+    Double,
+    /// `string`
+    String,
+}
+
+/// A typed GraphML attribute value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrValue {
+    /// A `boolean` value.
+    Boolean(bool),
+    /// An `int` value.
+    Int(i32),
+    /// A `long` value.
+    Long(i64),
+    /// A `float` value.
+    Float(f32),
+    /// A `double` value.
+    Double(f64),
+    /// A `string` value.
+    String(String),
+}
+
+/// An error produced while reading GraphML via [`Dag::from_graphml`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphMlError {
+    /// The input is not well-formed XML.
+    Xml {
+        /// The byte offset within the input at which the error occurred.
+        position: u64,
+        /// A description of the error.
+        message: String,
+    },
+    /// The document does not contain a `graph` element.
+    MissingGraph,
+    /// The graph is not declared with `edgedefault="directed"`, or contains an undirected edge.
+    Undirected,
+    /// An element is missing a required attribute.
+    MissingAttribute {
+        /// The name of the element.
+        element: String,
+        /// The name of the missing attribute.
+        attribute: String,
+    },
+    /// A key has an `attr.type` that is not one of the GraphML types.
+    UnknownType(String),
+    /// A `data` element refers to a key that has not been declared.
+    UnknownKey(String),
+    /// A value could not be parsed as the type of its key.
+    InvalidValue {
+        /// The key of the attribute.
+        key: String,
+        /// The value that could not be parsed.
+        value: String,
+    },
+    /// Two nodes share the same ID.
+    DuplicateNode(String),
+    /// An edge refers to a node ID that does not exist.
+    UnknownNode(String),
+    /// The input uses a GraphML feature that is not supported, e.g. nested graphs or hyperedges.
+    Unsupported(String),
+    /// The edge between the nodes with the given IDs would have created a cycle.
+    WouldCycle {
+        /// The ID of the edge's source node.
+        source: String,
+        /// The ID of the edge's target node.
+        target: String,
+    },
+}
+
+impl AttrType {
+    /// The name of the type as used by `attr.type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AttrType::Boolean => "boolean",
+            AttrType::Int => "int",
+            AttrType::Long => "long",
+            AttrType::Float => "float",
+            AttrType::Double => "double",
+            AttrType::String => "string",
+        }
+    }
+
+    fn from_name(s: &str) -> Option<Self> {
+        let ty = match s {
+            "boolean" => AttrType::Boolean,
+            "int" => AttrType::Int,
+            "long" => AttrType::Long,
+            "float" => AttrType::Float,
+            "double" => AttrType::Double,
+            "string" => AttrType::String,
+            _ => return None,
+        };
+        Some(ty)
+    }
+
+    // Parse a value of this type, returning `None` if it is invalid.
+    fn parse(&self, s: &str) -> Option<AttrValue> {
+        let t = s.trim();
+        let value = match self {
+            AttrType::Boolean => match t {
+                "true" | "1" => AttrValue::Boolean(true),
+                "false" | "0" => AttrValue::Boolean(false),
+                _ => return None,
+            },
+            AttrType::Int => AttrValue::Int(t.parse().ok()?),
+            AttrType::Long => AttrValue::Long(t.parse().ok()?),
+            AttrType::Float => AttrValue::Float(t.parse().ok()?),
+            AttrType::Double => AttrValue::Double(t.parse().ok()?),
+            AttrType::String => AttrValue::String(s.to_string()),
+        };
+        Some(value)
+    }
+}
+
+impl AttrValue {
+    /// The type of the value.
+    pub fn attr_type(&self) -> AttrType {
+        match self {
+            AttrValue::Boolean(_) => AttrType::Boolean,
+            AttrValue::Int(_) => AttrType::Int,
+            AttrValue::Long(_) => AttrType::Long,
+            AttrValue::Float(_) => AttrType::Float,
+            AttrValue::Double(_) => AttrType::Double,
+            AttrValue::String(_) => AttrType::String,
+        }
+    }
+
+    // Write the value in its GraphML (XML Schema) representation.
+    fn write(&self, out: &mut String) {
+        fn write_float(out: &mut String, f: f64) {
+            if f.is_infinite() {
+                out.push_str(if f > 0.0 { "INF" } else { "-INF" });
+            } else {
+                write!(out, "{}", f).unwrap();
+            }
+        }
+        match self {
+            AttrValue::Boolean(b) => write!(out, "{}", b).unwrap(),
+            AttrValue::Int(i) => write!(out, "{}", i).unwrap(),
+            AttrValue::Long(l) => write!(out, "{}", l).unwrap(),
+            AttrValue::Float(f) if f.is_finite() => write!(out, "{}", f).unwrap(),
+            AttrValue::Float(f) => write_float(out, *f as f64),
+            AttrValue::Double(d) => write_float(out, *d),
+            AttrValue::String(s) => out.push_str(&escape(s.as_str())),
+        }
+    }
+}
+
+impl From<bool> for AttrValue {
+    fn from(b: bool) -> Self {
+        AttrValue::Boolean(b)
+    }
+}
+
+impl From<i32> for AttrValue {
+    fn from(i: i32) -> Self {
+        AttrValue::Int(i)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(l: i64) -> Self {
+        AttrValue::Long(l)
+    }
+}
+
+impl From<f32> for AttrValue {
+    fn from(f: f32) -> Self {
+        AttrValue::Float(f)
+    }
+}
+
+impl From<f64> for AttrValue {
+    fn from(d: f64) -> Self {
+        AttrValue::Double(d)
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(s: String) -> Self {
+        AttrValue::String(s)
+    }
+}
+
+impl<'a> From<&'a str> for AttrValue {
+    fn from(s: &'a str) -> Self {
+        AttrValue::String(s.to_string())
+    }
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the `Dag` as a GraphML document.
+    ///
+    /// Nodes are given the IDs `n0`, `n1`, etc. and edges `e0`, `e1`, etc. by index. The
+    /// attributes of each node and edge are produced by `node_attrs` and `edge_attrs`, and a key
+    /// is declared for each distinct attribute name with the type of its values.
+    ///
+    /// **Panics** if values of different types are given for the same attribute name.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    /// use daggy::graphml::{AttrValue, Attributes};
+    ///
+    /// let mut dag = Dag::<&str, f64>::new();
+    /// let root = dag.add_node("root");
+    /// dag.add_child(root, 0.5, "child");
+    ///
+    /// let xml = dag.to_graphml(
+    ///     |_, n| Attributes::from([("label".into(), AttrValue::from(*n))]),
+    ///     |_, e| Attributes::from([("weight".into(), AttrValue::from(*e))]),
+    /// );
+    /// let parsed = Dag::from_graphml(
+    ///     &xml,
+    ///     |_, attrs| attrs["label"].clone(),
+    ///     |attrs| attrs["weight"].clone(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(parsed[root], AttrValue::from("root"));
+    /// assert_eq!(parsed.raw_edges()[0].weight, AttrValue::Double(0.5));
+    /// ```
+    pub fn to_graphml<F, G>(&self, mut node_attrs: F, mut edge_attrs: G) -> String
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> Attributes,
+        G: FnMut(EdgeIndex<Ix>, &E) -> Attributes,
+    {
+        let nodes = self
+            .graph
+            .raw_nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i, node_attrs(NodeIndex::new(i), &n.weight)))
+            .collect();
+        let edges = self
+            .graph
+            .raw_edges()
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let attrs = edge_attrs(EdgeIndex::new(i), &e.weight);
+                (i, e.source().index(), e.target().index(), attrs)
+            })
+            .collect();
+        write_graphml(nodes, edges)
+    }
+}
+
+#[cfg(feature = "stable_dag")]
+impl<N, E, Ix> crate::stable_dag::StableDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the `StableDag` as a GraphML document.
+    ///
+    /// As with [`Dag::to_graphml`], nodes and edges are identified by their indices, so the IDs
+    /// of vacant indices are skipped.
+    ///
+    /// **Panics** if values of different types are given for the same attribute name.
+    pub fn to_graphml<F, G>(&self, mut node_attrs: F, mut edge_attrs: G) -> String
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> Attributes,
+        G: FnMut(EdgeIndex<Ix>, &E) -> Attributes,
+    {
+        use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+        let graph = self.graph();
+        let nodes = graph
+            .node_indices()
+            .map(|n| (n.index(), node_attrs(n, &graph[n])))
+            .collect();
+        let edges = graph
+            .edge_references()
+            .map(|e| {
+                let attrs = edge_attrs(e.id(), e.weight());
+                (
+                    e.id().index(),
+                    e.source().index(),
+                    e.target().index(),
+                    attrs,
+                )
+            })
+            .collect();
+        write_graphml(nodes, edges)
+    }
+}
+
+impl<N, E> Dag<N, E, DefaultIx> {
+    /// Read a GraphML document into a `Dag`.
+    ///
+    /// The first `graph` within the document is read, and must be declared with
+    /// `edgedefault="directed"`. Nodes are added in document order. The weight of each node is
+    /// produced by `node_weight` from its ID and attributes, and the weight of each edge by
+    /// `edge_weight` from its attributes. Attributes missing from an element take the default
+    /// value of their key, if any. Keys without an `attr.name`, such as yEd's graphics keys, are
+    /// ignored.
+    ///
+    /// Returns an error if the document is malformed, contains an undirected edge, uses nested
+    /// graphs or hyperedges, or if any of its edges would create a cycle.
+    pub fn from_graphml<F, G>(
+        xml: &str,
+        mut node_weight: F,
+        mut edge_weight: G,
+    ) -> Result<Self, GraphMlError>
+    where
+        F: FnMut(&str, &Attributes) -> N,
+        G: FnMut(&Attributes) -> E,
+    {
+        let doc = parse(xml)?;
+        let mut dag = Dag::with_capacity(doc.nodes.len(), doc.edges.len());
+        let mut indices = HashMap::with_capacity(doc.nodes.len());
+        for (id, attrs) in &doc.nodes {
+            if indices.contains_key(id.as_str()) {
+                return Err(GraphMlError::DuplicateNode(id.clone()));
+            }
+            let node = dag.add_node(node_weight(id, attrs));
+            indices.insert(id.as_str(), node);
+        }
+        for (source, target, attrs) in &doc.edges {
+            let index = |id: &String| match indices.get(id.as_str()) {
+                Some(&node) => Ok(node),
+                None => Err(GraphMlError::UnknownNode(id.clone())),
+            };
+            let (a, b) = (index(source)?, index(target)?);
+            if dag.add_edge(a, b, edge_weight(attrs)).is_err() {
+                return Err(GraphMlError::WouldCycle {
+                    source: source.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+        Ok(dag)
+    }
+}
+
+// A key declared for either nodes or edges.
+struct KeyDecl<'a> {
+    id: String,
+    name: &'a str,
+    ty: AttrType,
+}
+
+// Declare a key for every attribute name, in order of first appearance.
+fn declare_keys<'a, I>(keys: &mut Vec<KeyDecl<'a>>, attrs: I)
+where
+    I: Iterator<Item = &'a Attributes>,
+{
+    let start = keys.len();
+    for attrs in attrs {
+        for (name, value) in attrs {
+            let ty = value.attr_type();
+            match keys[start..].iter().find(|k| k.name == name) {
+                Some(key) => assert!(
+                    key.ty == ty,
+                    "attribute {:?} has values of both type {} and {}",
+                    name,
+                    key.ty.as_str(),
+                    ty.as_str(),
+                ),
+                None => {
+                    let id = format!("d{}", keys.len());
+                    keys.push(KeyDecl { id, name, ty });
+                }
+            }
+        }
+    }
+}
+
+fn write_data(out: &mut String, keys: &[KeyDecl], attrs: &Attributes) {
+    for (name, value) in attrs {
+        let key = keys
+            .iter()
+            .find(|k| k.name == name)
+            .expect("undeclared key");
+        write!(out, "      <data key=\"{}\">", key.id).unwrap();
+        value.write(out);
+        out.push_str("</data>\n");
+    }
+}
+
+type NodeEntry = (usize, Attributes);
+type EdgeEntry = (usize, usize, usize, Attributes);
+
+fn write_graphml(nodes: Vec<NodeEntry>, edges: Vec<EdgeEntry>) -> String {
+    let mut node_keys = vec![];
+    declare_keys(&mut node_keys, nodes.iter().map(|(_, a)| a));
+    let mut edge_keys = vec![];
+    declare_keys(&mut edge_keys, edges.iter().map(|(_, _, _, a)| a));
+    // Number the edge keys after the node keys.
+    for (i, key) in edge_keys.iter_mut().enumerate() {
+        key.id = format!("d{}", node_keys.len() + i);
+    }
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(concat!(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\"",
+        " xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\"",
+        " xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns",
+        " http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    ));
+    for (keys, domain) in [(&node_keys, "node"), (&edge_keys, "edge")] {
+        for key in keys.iter() {
+            writeln!(
+                out,
+                "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>",
+                key.id,
+                domain,
+                escape(key.name),
+                key.ty.as_str(),
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+    for (i, attrs) in &nodes {
+        if attrs.is_empty() {
+            writeln!(out, "    <node id=\"n{}\"/>", i).unwrap();
+        } else {
+            writeln!(out, "    <node id=\"n{}\">", i).unwrap();
+            write_data(&mut out, &node_keys, attrs);
+            out.push_str("    </node>\n");
+        }
+    }
+    for (i, a, b, attrs) in &edges {
+        write!(
+            out,
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"",
+            i, a, b
+        )
+        .unwrap();
+        if attrs.is_empty() {
+            out.push_str("/>\n");
+        } else {
+            out.push_str(">\n");
+            write_data(&mut out, &edge_keys, attrs);
+            out.push_str("    </edge>\n");
+        }
+    }
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+// A key as declared within a document being read.
+struct Key {
+    domain: String,
+    name: Option<String>,
+    ty: AttrType,
+    default: Option<AttrValue>,
+}
+
+// The nodes and edges of a document, by ID, prior to building the `Dag`.
+#[derive(Default)]
+struct Document {
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+// The element to which `data` elements currently apply.
+#[derive(Copy, Clone, PartialEq)]
+enum Owner {
+    None,
+    Key,
+    Graph,
+    Node,
+    Edge,
+}
+
+fn parse(xml: &str) -> Result<Document, GraphMlError> {
+    let mut reader = Reader::from_str(xml);
+    let xml_error = |reader: &Reader<&[u8]>, e: &dyn std::fmt::Display| GraphMlError::Xml {
+        position: reader.error_position(),
+        message: e.to_string(),
+    };
+    let mut keys: HashMap<String, Key> = HashMap::new();
+    let mut key_ids = vec![];
+    let mut doc = Document::default();
+    let mut owner = Owner::None;
+    let mut graph_seen = false;
+    loop {
+        let event = reader.read_event().map_err(|e| xml_error(&reader, &e))?;
+        let (e, is_empty) = match event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"graph" | b"key" => owner = Owner::None,
+                    b"node" | b"edge" => owner = Owner::Graph,
+                    _ => (),
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        let local_name = e.local_name();
+        let name = std::str::from_utf8(local_name.as_ref()).unwrap_or("");
+        match name {
+            "graphml" => (),
+            "key" => {
+                let id = required(&reader, &e, "key", "id")?;
+                let domain = attribute(&reader, &e, "for")?.unwrap_or_else(|| "all".into());
+                let key_name = attribute(&reader, &e, "attr.name")?;
+                let ty = match attribute(&reader, &e, "attr.type")? {
+                    None => AttrType::String,
+                    Some(ty) => AttrType::from_name(&ty).ok_or(GraphMlError::UnknownType(ty))?,
+                };
+                let key = Key {
+                    domain,
+                    name: key_name,
+                    ty,
+                    default: None,
+                };
+                keys.insert(id.clone(), key);
+                key_ids.push(id);
+                if !is_empty {
+                    owner = Owner::Key;
+                }
+            }
+            "default" if owner == Owner::Key && !is_empty => {
+                let text = read_text(&mut reader, &e)?;
+                let id = key_ids.last().expect("no key");
+                let key = keys.get_mut(id).expect("no key");
+                key.default = Some(parse_value(id, key.ty, &text)?);
+            }
+            "graph" if owner == Owner::Node || owner == Owner::Edge || owner == Owner::Graph => {
+                return Err(GraphMlError::Unsupported("nested graphs".into()));
+            }
+            // Only the first graph within the document is read.
+            "graph" if graph_seen => skip(&mut reader, &e, is_empty)?,
+            "graph" => {
+                if attribute(&reader, &e, "edgedefault")?.as_deref() != Some("directed") {
+                    return Err(GraphMlError::Undirected);
+                }
+                graph_seen = true;
+                if !is_empty {
+                    owner = Owner::Graph;
+                }
+            }
+            "node" if owner == Owner::Graph => {
+                let id = required(&reader, &e, "node", "id")?;
+                doc.nodes.push((id, defaults(&keys, "node")));
+                if !is_empty {
+                    owner = Owner::Node;
+                }
+            }
+            "edge" if owner == Owner::Graph => {
+                if attribute(&reader, &e, "directed")?.as_deref() == Some("false") {
+                    return Err(GraphMlError::Undirected);
+                }
+                let source = required(&reader, &e, "edge", "source")?;
+                let target = required(&reader, &e, "edge", "target")?;
+                doc.edges.push((source, target, defaults(&keys, "edge")));
+                if !is_empty {
+                    owner = Owner::Edge;
+                }
+            }
+            "hyperedge" => return Err(GraphMlError::Unsupported("hyperedges".into())),
+            "data" if owner == Owner::Node || owner == Owner::Edge => {
+                let id = required(&reader, &e, "data", "key")?;
+                let key = keys
+                    .get(&id)
+                    .ok_or_else(|| GraphMlError::UnknownKey(id.clone()))?;
+                let key_name = match &key.name {
+                    Some(name) => name.clone(),
+                    None => {
+                        skip(&mut reader, &e, is_empty)?;
+                        continue;
+                    }
+                };
+                let text = match is_empty {
+                    true => String::new(),
+                    false => read_text(&mut reader, &e)?,
+                };
+                let value = parse_value(&id, key.ty, &text)?;
+                let attrs = match owner {
+                    Owner::Node => &mut doc.nodes.last_mut().expect("no node").1,
+                    _ => &mut doc.edges.last_mut().expect("no edge").2,
+                };
+                attrs.insert(key_name, value);
+            }
+            // Descriptions, ports, graph data and any extensions are ignored.
+            _ => skip(&mut reader, &e, is_empty)?,
+        }
+    }
+    if !graph_seen {
+        return Err(GraphMlError::MissingGraph);
+    }
+    Ok(doc)
+}
+
+// The default values of all named keys that apply to the given domain.
+fn defaults(keys: &HashMap<String, Key>, domain: &str) -> Attributes {
+    keys.values()
+        .filter(|k| k.domain == domain || k.domain == "all")
+        .filter_map(|k| Some((k.name.clone()?, k.default.clone()?)))
+        .collect()
+}
+
+fn parse_value(key: &str, ty: AttrType, text: &str) -> Result<AttrValue, GraphMlError> {
+    ty.parse(text).ok_or_else(|| GraphMlError::InvalidValue {
+        key: key.to_string(),
+        value: text.to_string(),
+    })
+}
+
+fn attribute(
+    reader: &Reader<&[u8]>,
+    e: &BytesStart,
+    name: &str,
+) -> Result<Option<String>, GraphMlError> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(|err| GraphMlError::Xml {
+            position: reader.buffer_position(),
+            message: err.to_string(),
+        })?;
+        if attr.key.as_ref() == name.as_bytes() {
+            let value = attr.unescape_value().map_err(|err| GraphMlError::Xml {
+                position: reader.buffer_position(),
+                message: err.to_string(),
+            })?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required(
+    reader: &Reader<&[u8]>,
+    e: &BytesStart,
+    element: &str,
+    name: &str,
+) -> Result<String, GraphMlError> {
+    attribute(reader, e, name)?.ok_or_else(|| GraphMlError::MissingAttribute {
+        element: element.to_string(),
+        attribute: name.to_string(),
+    })
+}
+
+// Read the unescaped text content up to the end of the given element.
+fn read_text(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Result<String, GraphMlError> {
+    let raw = reader
+        .read_text(e.name())
+        .map_err(|err| GraphMlError::Xml {
+            position: reader.error_position(),
+            message: err.to_string(),
+        })?;
+    let text = unescape(&raw).map_err(|err| GraphMlError::Xml {
+        position: reader.buffer_position(),
+        message: err.to_string(),
+    })?;
+    Ok(text.into_owned())
+}
+
+// Skip the given element along with all of its content.
+fn skip(reader: &mut Reader<&[u8]>, e: &BytesStart, is_empty: bool) -> Result<(), GraphMlError> {
+    if !is_empty {
+        reader
+            .read_to_end(e.name())
+            .map_err(|err| GraphMlError::Xml {
+                position: reader.error_position(),
+                message: err.to_string(),
+            })?;
+    }
+    Ok(())
+}
+
+impl std::fmt::Display for GraphMlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GraphMlError::Xml { position, message } => {
+                write!(f, "malformed XML at byte {}: {}", position, message)
+            }
+            GraphMlError::MissingGraph => write!(f, "the document contains no graph"),
+            GraphMlError::Undirected => write!(f, "expected a directed graph"),
+            GraphMlError::MissingAttribute { element, attribute } => {
+                write!(f, "`{}` element is missing `{}`", element, attribute)
+            }
+            GraphMlError::UnknownType(ty) => write!(f, "unknown attribute type {:?}", ty),
+            GraphMlError::UnknownKey(key) => write!(f, "undeclared key {:?}", key),
+            GraphMlError::InvalidValue { key, value } => {
+                write!(f, "invalid value {:?} for key {:?}", value, key)
+            }
+            GraphMlError::DuplicateNode(id) => write!(f, "duplicate node ID {:?}", id),
+            GraphMlError::UnknownNode(id) => write!(f, "edge refers to unknown node {:?}", id),
+            GraphMlError::Unsupported(what) => write!(f, "{} are not supported", what),
+            GraphMlError::WouldCycle { source, target } => {
+                write!(f, "edge {:?} -> {:?} would create a cycle", source, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphMlError {}
@@ -0,0 +1,380 @@
+//! This module includes the implementation of the **HistoryDag** data structure. The
+//! **HistoryDag** wraps a **Dag** and records every mutation as an invertible edit, allowing
+//! changes to be undone and redone.
+
+use crate::edit::Edit;
+use crate::{Dag, EdgeIndex, NodeIndex, WouldCycle};
+use petgraph::graph::{DefaultIx, IndexType};
+use std::collections::VecDeque;
+
+/// A **Dag** that records the history of its mutations for undo and redo.
+///
+/// Every mutating method records the edit that reverses it. Edits are grouped into steps by
+/// [`checkpoint`](HistoryDag::checkpoint): all edits made since the last checkpoint are undone or
+/// redone together.
+///
+/// As each step is only ever reversed back to a state that the **Dag** was previously in (and
+/// each of those states was acyclic), undoing and redoing can never produce a cycle. Indices are
+/// also restored exactly, including those shifted by `remove_node` and `remove_edge`, as is the
+/// order in which each node's neighbours are visited.
+#[derive(Clone, Debug)]
+pub struct HistoryDag<N, E, Ix: IndexType = DefaultIx> {
+    dag: Dag<N, E, Ix>,
+    // The edits made since the last checkpoint.
+    pending: Step<N, E, Ix>,
+    undo: VecDeque<Step<N, E, Ix>>,
+    redo: Vec<Step<N, E, Ix>>,
+    max_history: Option<usize>,
+}
+
+// A group of edits that are undone or redone together.
+#[derive(Clone, Debug)]
+struct Step<N, E, Ix> {
+    edits: Vec<Edit<N, E, Ix>>,
+    // The age of each edge in the state that applying the step returns to, if the step reverses
+    // a removal and so must restore the order of each node's neighbours.
+    ages: Option<Vec<usize>>,
+}
+
+impl<N, E, Ix> HistoryDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Create a new, empty `HistoryDag` with unbounded history.
+    pub fn new() -> Self {
+        Self::from_dag(Dag::new())
+    }
+
+    /// Begin recording history for the given `Dag`.
+    pub fn from_dag(dag: Dag<N, E, Ix>) -> Self {
+        HistoryDag {
+            dag,
+            pending: Step::default(),
+            undo: VecDeque::new(),
+            redo: vec![],
+            max_history: None,
+        }
+    }
+
+    /// Limit the number of steps that may be undone, discarding the oldest steps first.
+    pub fn with_max_history(mut self, steps: usize) -> Self {
+        self.set_max_history(Some(steps));
+        self
+    }
+
+    /// Limit the number of steps that may be undone, or remove the limit with `None`.
+    ///
+    /// If the history already exceeds the given limit, the oldest steps are discarded.
+    pub fn set_max_history(&mut self, steps: Option<usize>) {
+        self.max_history = steps;
+        self.truncate_history();
+    }
+
+    /// Borrow the inner `Dag`.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        &self.dag
+    }
+
+    /// Take ownership of the `HistoryDag` and return the inner `Dag`, discarding the history.
+    pub fn into_dag(self) -> Dag<N, E, Ix> {
+        self.dag
+    }
+
+    /// Group all edits made since the previous checkpoint into a single undoable step.
+    ///
+    /// Does nothing if no edits have been made since the previous checkpoint.
+    pub fn checkpoint(&mut self) {
+        if self.pending.edits.is_empty() {
+            return;
+        }
+        let step = std::mem::take(&mut self.pending);
+        self.undo.push_back(step);
+        self.truncate_history();
+    }
+
+    /// Whether or not there is a step that may be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.pending.edits.is_empty() || !self.undo.is_empty()
+    }
+
+    /// Whether or not there is a step that may be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Undo the most recent step, first sealing any edits made since the last checkpoint into a
+    /// step of their own.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.checkpoint();
+        let step = match self.undo.pop_back() {
+            Some(step) => step,
+            None => return false,
+        };
+        let inverse = self.apply_step(step);
+        self.redo.push(inverse);
+        true
+    }
+
+    /// Redo the most recently undone step.
+    ///
+    /// Any new mutation clears the steps available for redo.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let step = match self.redo.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+        let inverse = self.apply_step(step);
+        self.undo.push_back(inverse);
+        self.truncate_history();
+        true
+    }
+
+    /// Discard all undo and redo history, keeping the current state of the `Dag`.
+    pub fn clear_history(&mut self) {
+        self.pending = Step::default();
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// The total number of nodes in the **HistoryDag**.
+    pub fn node_count(&self) -> usize {
+        self.dag.node_count()
+    }
+
+    /// The total number of edges in the **HistoryDag**.
+    pub fn edge_count(&self) -> usize {
+        self.dag.edge_count()
+    }
+
+    /// Add a new node to the `Dag` with the given weight.
+    ///
+    /// See [`Dag::add_node`].
+    pub fn add_node(&mut self, weight: N) -> NodeIndex<Ix> {
+        let node = self.dag.add_node(weight);
+        self.record(Edit::RemoveNode(node));
+        node
+    }
+
+    /// Add a new directed edge `a` -> `b` with the given weight.
+    ///
+    /// See [`Dag::add_edge`]. Nothing is recorded if the edge would create a cycle.
+    pub fn add_edge(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle<E>> {
+        let edge = self.dag.add_edge(a, b, weight)?;
+        self.record(Edit::RemoveEdge(edge));
+        Ok(edge)
+    }
+
+    /// Add a new edge and child node to the node at the given index.
+    ///
+    /// See [`Dag::add_child`].
+    pub fn add_child(
+        &mut self,
+        parent: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let (edge, node) = self.dag.add_child(parent, edge, node);
+        self.record(Edit::RemoveNode(node));
+        self.record(Edit::RemoveEdge(edge));
+        (edge, node)
+    }
+
+    /// Add a new edge and parent node to the node at the given index.
+    ///
+    /// See [`Dag::add_parent`].
+    pub fn add_parent(
+        &mut self,
+        child: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let (edge, node) = self.dag.add_parent(child, edge, node);
+        self.record(Edit::RemoveNode(node));
+        self.record(Edit::RemoveEdge(edge));
+        (edge, node)
+    }
+
+    /// Update the weight of the edge `a` -> `b` if it exists, otherwise add it.
+    ///
+    /// See [`Dag::update_edge`]. Nothing is recorded if the edge would create a cycle.
+    pub fn update_edge(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle<E>> {
+        match self.dag.find_edge(a, b) {
+            Some(edge) => {
+                self.set_edge_weight(edge, weight);
+                Ok(edge)
+            }
+            None => self.add_edge(a, b, weight),
+        }
+    }
+
+    /// Remove the node at the given index along with all of its edges.
+    ///
+    /// See [`Dag::remove_node`]. The removed weight is retained in the history, so a clone is
+    /// returned.
+    pub fn remove_node(&mut self, node: NodeIndex<Ix>) -> Option<N>
+    where
+        N: Clone,
+    {
+        let weight = self.dag.node_weight(node)?.clone();
+        self.record_ages();
+        let inverse = self.dag.apply_edit(Edit::RemoveNode(node));
+        self.record(inverse);
+        Some(weight)
+    }
+
+    /// Remove the edge at the given index.
+    ///
+    /// See [`Dag::remove_edge`]. The removed weight is retained in the history, so a clone is
+    /// returned.
+    pub fn remove_edge(&mut self, edge: EdgeIndex<Ix>) -> Option<E>
+    where
+        E: Clone,
+    {
+        let weight = self.dag.edge_weight(edge)?.clone();
+        self.record_ages();
+        let inverse = self.dag.apply_edit(Edit::RemoveEdge(edge));
+        self.record(inverse);
+        Some(weight)
+    }
+
+    /// Replace the weight of the node at the given index.
+    ///
+    /// **Panics** if the node does not exist.
+    pub fn set_node_weight(&mut self, node: NodeIndex<Ix>, weight: N) {
+        let inverse = self.dag.apply_edit(Edit::NodeWeight(node, weight));
+        self.record(inverse);
+    }
+
+    /// Replace the weight of the edge at the given index.
+    ///
+    /// **Panics** if the edge does not exist.
+    pub fn set_edge_weight(&mut self, edge: EdgeIndex<Ix>, weight: E) {
+        let inverse = self.dag.apply_edit(Edit::EdgeWeight(edge, weight));
+        self.record(inverse);
+    }
+
+    /// Borrow the weight of the node at the given index.
+    pub fn node_weight(&self, node: NodeIndex<Ix>) -> Option<&N> {
+        self.dag.node_weight(node)
+    }
+
+    /// Mutably borrow the weight of the node at the given index.
+    ///
+    /// A clone of the current weight is recorded so that the edit may be undone.
+    pub fn node_weight_mut(&mut self, node: NodeIndex<Ix>) -> Option<&mut N>
+    where
+        N: Clone,
+    {
+        let old = self.dag.node_weight(node)?.clone();
+        self.record(Edit::NodeWeight(node, old));
+        self.dag.node_weight_mut(node)
+    }
+
+    /// Borrow the weight of the edge at the given index.
+    pub fn edge_weight(&self, edge: EdgeIndex<Ix>) -> Option<&E> {
+        self.dag.edge_weight(edge)
+    }
+
+    /// Mutably borrow the weight of the edge at the given index.
+    ///
+    /// A clone of the current weight is recorded so that the edit may be undone.
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex<Ix>) -> Option<&mut E>
+    where
+        E: Clone,
+    {
+        let old = self.dag.edge_weight(edge)?.clone();
+        self.record(Edit::EdgeWeight(edge, old));
+        self.dag.edge_weight_mut(edge)
+    }
+
+    fn record(&mut self, inverse: Edit<N, E, Ix>) {
+        self.redo.clear();
+        self.pending.edits.push(inverse);
+    }
+
+    // Record the age of every edge before the first removal since the last checkpoint, as
+    // reversing a removal is the only edit that changes the order of any adjacency list.
+    fn record_ages(&mut self) {
+        if self.pending.ages.is_none() {
+            self.pending.ages = Some(self.dag.edge_ages());
+        }
+    }
+
+    // Apply the edits of a step in reverse order, returning the step that reverses it.
+    fn apply_step(&mut self, mut step: Step<N, E, Ix>) -> Step<N, E, Ix> {
+        let removes = step
+            .edits
+            .iter()
+            .any(|edit| matches!(edit, Edit::RemoveNode(_) | Edit::RemoveEdge(_)));
+        let ages = match removes {
+            true => Some(self.dag.edge_ages()),
+            false => None,
+        };
+        let mut edits = Vec::with_capacity(step.edits.len());
+        while let Some(edit) = step.edits.pop() {
+            edits.push(self.dag.apply_edit(edit));
+        }
+        if let Some(ages) = step.ages {
+            self.dag.restore_adjacency(&ages);
+        }
+        Step { edits, ages }
+    }
+
+    fn truncate_history(&mut self) {
+        if let Some(max) = self.max_history {
+            while self.undo.len() > max {
+                self.undo.pop_front();
+            }
+        }
+    }
+}
+
+impl<N, E, Ix> Default for Step<N, E, Ix> {
+    fn default() -> Self {
+        Step {
+            edits: vec![],
+            ages: None,
+        }
+    }
+}
+
+impl<N, E, Ix> Default for HistoryDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        HistoryDag::new()
+    }
+}
+
+impl<N, E, Ix> From<Dag<N, E, Ix>> for HistoryDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    fn from(dag: Dag<N, E, Ix>) -> Self {
+        HistoryDag::from_dag(dag)
+    }
+}
+
+impl<N, E, Ix> From<HistoryDag<N, E, Ix>> for Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    fn from(val: HistoryDag<N, E, Ix>) -> Self {
+        val.into_dag()
+    }
+}
@@ -0,0 +1,263 @@
+//! This module includes the implementation of the **Incremental** data structure. The
+//! **Incremental** wraps a **Dag** and caches a value computed for each node from the values of
+//! its parents, recomputing only those values that may have been affected by a change.
+
+use crate::{Dag, EdgeIndex, NodeIndex, WouldCycle};
+use petgraph::graph::{DefaultIx, IndexType};
+use petgraph::visit::EdgeRef;
+use std::collections::VecDeque;
+
+/// The state of a node's cached value within an **Incremental**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeState {
+    /// The node's value is up to date.
+    Clean,
+    /// The node itself has changed and its value must be recomputed.
+    Dirty,
+    /// One of the node's ancestors has changed, so its value may need to be recomputed.
+    Stale,
+}
+
+/// A **Dag** along with a cached value of type **V** for each node, computed from the node's
+/// weight and the values of its parents.
+///
+/// Marking a node dirty marks all of its descendants as stale. [`recompute`] then visits the
+/// dirty and stale nodes in topological order, re-evaluating a node only if it is dirty or if the
+/// value of one of its parents changed during the same pass. This "early cutoff" means that a
+/// change which does not alter a node's value does not cause its descendants to be re-evaluated.
+///
+/// Mutations made via the **Incremental** mark the affected nodes dirty automatically.
+///
+/// [`recompute`]: Incremental::recompute
+#[derive(Clone, Debug)]
+pub struct Incremental<N, E, V, Ix: IndexType = DefaultIx> {
+    dag: Dag<N, E, Ix>,
+    values: Vec<Option<V>>,
+    states: Vec<NodeState>,
+}
+
+impl<N, E, V, Ix> Incremental<N, E, V, Ix>
+where
+    V: PartialEq,
+    Ix: IndexType,
+{
+    /// Create a new, empty `Incremental`.
+    pub fn new() -> Self {
+        Self::from_dag(Dag::new())
+    }
+
+    /// Wrap the given `Dag`, marking all of its nodes as dirty.
+    pub fn from_dag(dag: Dag<N, E, Ix>) -> Self {
+        let n = dag.node_count();
+        Incremental {
+            dag,
+            values: (0..n).map(|_| None).collect(),
+            states: vec![NodeState::Dirty; n],
+        }
+    }
+
+    /// Borrow the inner `Dag`.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        &self.dag
+    }
+
+    /// Take ownership of the `Incremental` and return the inner `Dag`, discarding all values.
+    pub fn into_dag(self) -> Dag<N, E, Ix> {
+        self.dag
+    }
+
+    /// The last computed value for the given node.
+    ///
+    /// Returns `None` if the node does not exist or if its value has never been computed. The
+    /// value may be out of date unless the node's state is `Clean`.
+    pub fn value(&self, node: NodeIndex<Ix>) -> Option<&V> {
+        self.values.get(node.index()).and_then(|v| v.as_ref())
+    }
+
+    /// The state of the given node's value, or `None` if the node does not exist.
+    pub fn state(&self, node: NodeIndex<Ix>) -> Option<NodeState> {
+        self.states.get(node.index()).copied()
+    }
+
+    /// Mark the given node as dirty and all of its descendants as stale.
+    ///
+    /// Descendants of nodes that are already dirty or stale are not revisited, so this computes
+    /// in time proportional to the number of newly affected nodes and their edges.
+    ///
+    /// **Panics** if the node does not exist.
+    pub fn mark_dirty(&mut self, node: NodeIndex<Ix>) {
+        let was_clean = self.states[node.index()] == NodeState::Clean;
+        self.states[node.index()] = NodeState::Dirty;
+        if !was_clean {
+            return;
+        }
+        let mut stack = vec![node];
+        while let Some(n) = stack.pop() {
+            for child in self.dag.graph.neighbors(n) {
+                let state = &mut self.states[child.index()];
+                if *state == NodeState::Clean {
+                    *state = NodeState::Stale;
+                    stack.push(child);
+                }
+            }
+        }
+    }
+
+    /// Re-evaluate the values of all dirty and stale nodes in topological order.
+    ///
+    /// `compute` is called with the node's index and weight along with each of the node's
+    /// incoming edge weights paired with the value of the parent on the other end.
+    ///
+    /// A stale node is only re-evaluated if one of its parents' values changed during this pass.
+    /// Returns the number of nodes that were re-evaluated.
+    ///
+    /// Computes in **O(n + e')** time plus the cost of each evaluation, where **e'** is the
+    /// number of edges connected to the dirty and stale nodes.
+    pub fn recompute<F>(&mut self, mut compute: F) -> usize
+    where
+        F: FnMut(NodeIndex<Ix>, &N, &[(&E, &V)]) -> V,
+    {
+        // Count the affected parents of each affected node.
+        let mut affected_parents = vec![0; self.states.len()];
+        let mut queue = VecDeque::new();
+        for (i, &state) in self.states.iter().enumerate() {
+            if state == NodeState::Clean {
+                continue;
+            }
+            let node = NodeIndex::new(i);
+            let count = self
+                .dag
+                .graph
+                .neighbors_directed(node, petgraph::Incoming)
+                .filter(|p| self.states[p.index()] != NodeState::Clean)
+                .count();
+            affected_parents[i] = count;
+            if count == 0 {
+                queue.push_back(node);
+            }
+        }
+
+        let mut changed = vec![false; self.states.len()];
+        let mut evaluated = 0;
+        while let Some(node) = queue.pop_front() {
+            let i = node.index();
+            let parents_changed = self
+                .dag
+                .graph
+                .neighbors_directed(node, petgraph::Incoming)
+                .any(|p| changed[p.index()]);
+            if self.states[i] == NodeState::Dirty || parents_changed {
+                let value = {
+                    let inputs: Vec<_> = self
+                        .dag
+                        .graph
+                        .edges_directed(node, petgraph::Incoming)
+                        .map(|e| {
+                            let value = self.values[e.source().index()].as_ref();
+                            (e.weight(), value.expect("parent value was not computed"))
+                        })
+                        .collect();
+                    compute(node, &self.dag[node], &inputs)
+                };
+                changed[i] = self.values[i].as_ref() != Some(&value);
+                self.values[i] = Some(value);
+                evaluated += 1;
+            }
+            self.states[i] = NodeState::Clean;
+            for child in self.dag.graph.neighbors(node) {
+                let count = &mut affected_parents[child.index()];
+                if self.states[child.index()] != NodeState::Clean {
+                    *count -= 1;
+                    if *count == 0 {
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+        evaluated
+    }
+
+    /// Add a new, dirty node with the given weight.
+    pub fn add_node(&mut self, weight: N) -> NodeIndex<Ix> {
+        let node = self.dag.add_node(weight);
+        self.values.push(None);
+        self.states.push(NodeState::Dirty);
+        node
+    }
+
+    /// Add a new directed edge `a` -> `b`, marking `b` as dirty.
+    ///
+    /// See [`Dag::add_edge`].
+    pub fn add_edge(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle<E>> {
+        let edge = self.dag.add_edge(a, b, weight)?;
+        self.mark_dirty(b);
+        Ok(edge)
+    }
+
+    /// Add a new edge and dirty child node to the node at the given index.
+    ///
+    /// See [`Dag::add_child`].
+    pub fn add_child(
+        &mut self,
+        parent: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let child = self.add_node(node);
+        let edge = self.dag.graph.add_edge(parent, child, edge);
+        (edge, child)
+    }
+
+    /// Remove the edge at the given index, marking its target as dirty.
+    ///
+    /// See [`Dag::remove_edge`].
+    pub fn remove_edge(&mut self, edge: EdgeIndex<Ix>) -> Option<E> {
+        let (_, target) = self.dag.edge_endpoints(edge)?;
+        self.mark_dirty(target);
+        self.dag.remove_edge(edge)
+    }
+
+    /// Remove the node at the given index, marking its children as dirty.
+    ///
+    /// As with [`Dag::remove_node`], the last node is moved into the index of the removed node,
+    /// along with its value and state.
+    pub fn remove_node(&mut self, node: NodeIndex<Ix>) -> Option<N> {
+        self.dag.node_weight(node)?;
+        let children: Vec<_> = self.dag.graph.neighbors(node).collect();
+        for child in children {
+            self.mark_dirty(child);
+        }
+        self.values.swap_remove(node.index());
+        self.states.swap_remove(node.index());
+        self.dag.remove_node(node)
+    }
+
+    /// Mutably borrow the weight of the given node, marking it as dirty.
+    pub fn node_weight_mut(&mut self, node: NodeIndex<Ix>) -> Option<&mut N> {
+        self.dag.node_weight(node)?;
+        self.mark_dirty(node);
+        self.dag.node_weight_mut(node)
+    }
+
+    /// Mutably borrow the weight of the given edge, marking its target as dirty.
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex<Ix>) -> Option<&mut E> {
+        let (_, target) = self.dag.edge_endpoints(edge)?;
+        self.mark_dirty(target);
+        self.dag.edge_weight_mut(edge)
+    }
+}
+
+impl<N, E, V, Ix> Default for Incremental<N, E, V, Ix>
+where
+    V: PartialEq,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        Incremental::new()
+    }
+}
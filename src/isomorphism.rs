@@ -0,0 +1,370 @@
+//! Isomorphism testing and canonical labelling of **Dag**s.
+
+use crate::{Dag, NodeIndex};
+use petgraph as pg;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+use std::collections::HashMap;
+
+// Properties of a node that are preserved by any isomorphism: the length of the longest paths to
+// the node from a root and from the node to a leaf, along with its in and out degrees.
+type Invariant = (usize, usize, usize, usize);
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Whether or not `self` and `other` are the same up to the relabelling of node indices.
+    ///
+    /// `node_eq` and `edge_eq` are used to compare the weights of candidate node and edge pairs
+    /// and are expected to behave as equivalence relations.
+    ///
+    /// Each node is only considered as a match for nodes within the same topological layer (the
+    /// longest path from a root), with the same height (the longest path to a leaf) and with the
+    /// same in and out degrees. Nodes are then matched in topological order so that each
+    /// candidate's incoming edges may be checked against its already matched parents.
+    pub fn is_isomorphic_to<N2, E2, Ix2, F, G>(
+        &self,
+        other: &Dag<N2, E2, Ix2>,
+        mut node_eq: F,
+        mut edge_eq: G,
+    ) -> bool
+    where
+        Ix2: IndexType,
+        F: FnMut(&N, &N2) -> bool,
+        G: FnMut(&E, &E2) -> bool,
+    {
+        let n = self.node_count();
+        if n != other.node_count() || self.edge_count() != other.edge_count() {
+            return false;
+        }
+
+        let self_invariants = invariants(self);
+        let other_invariants = invariants(other);
+        let mut sorted = self_invariants.clone();
+        let mut other_sorted = other_invariants.clone();
+        sorted.sort_unstable();
+        other_sorted.sort_unstable();
+        if sorted != other_sorted {
+            return false;
+        }
+        let mut candidates: HashMap<Invariant, Vec<NodeIndex<Ix2>>> = HashMap::new();
+        for (i, inv) in other_invariants.into_iter().enumerate() {
+            candidates.entry(inv).or_default().push(NodeIndex::new(i));
+        }
+
+        let order = self.topological_order();
+        let mut mapping: Vec<Option<NodeIndex<Ix2>>> = vec![None; n];
+        let mut used = vec![false; n];
+        let mut next_candidate = vec![0; n];
+        let mut depth = 0;
+        while depth < n {
+            let u = order[depth];
+            if let Some(v) = mapping[u.index()].take() {
+                used[v.index()] = false;
+            }
+            let cands = &candidates[&self_invariants[u.index()]];
+            let mut matched = false;
+            while next_candidate[depth] < cands.len() {
+                let v = cands[next_candidate[depth]];
+                next_candidate[depth] += 1;
+                if used[v.index()] || !node_eq(&self[u], &other[v]) {
+                    continue;
+                }
+                if parents_match(self, other, &mapping, u, v, &mut edge_eq) {
+                    mapping[u.index()] = Some(v);
+                    used[v.index()] = true;
+                    matched = true;
+                    break;
+                }
+            }
+            if matched {
+                depth += 1;
+            } else {
+                next_candidate[depth] = 0;
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+        }
+        true
+    }
+
+    /// The node indices of the `Dag` in a canonical order.
+    ///
+    /// Two `Dag`s that are isomorphic (with equal weights) always produce orders that map
+    /// corresponding nodes to the same positions, regardless of their original indices.
+    ///
+    /// Nodes are first distinguished by iteratively refining their weights with the weights and
+    /// colours of their parents and children. Remaining ties are broken by trying each candidate
+    /// in turn and keeping the order that produces the lexicographically smallest graph, skipping
+    /// candidates that are interchangeable "twins" (same weight, parents and children). This is
+    /// fast for typical DAGs, though may take exponential time for graphs with large amounts of
+    /// non-trivial symmetry.
+    pub fn canonical_order(&self) -> Vec<NodeIndex<Ix>>
+    where
+        N: Ord,
+        E: Ord,
+    {
+        let node_ranks = ranks(self.raw_nodes().iter().map(|n| &n.weight));
+        let edge_ranks = ranks(self.raw_edges().iter().map(|e| &e.weight));
+        let colors = refine(self, &edge_ranks, node_ranks.clone());
+        let mut best = None;
+        search(self, &node_ranks, &edge_ranks, colors, &mut best);
+        let (_, order) = best.expect("search produces at least one order");
+        order
+    }
+
+    /// Create a copy of the `Dag` with nodes and edges reordered into a canonical form.
+    ///
+    /// Nodes are ordered as in [`canonical_order`](Dag::canonical_order), and edges are sorted by
+    /// their source and target positions and then by weight. As a result, two `Dag`s that are the
+    /// same up to the relabelling of indices produce identical canonical forms, which will in
+    /// turn serialize to identical bytes.
+    pub fn canonical_form(&self) -> Self
+    where
+        N: Clone + Ord,
+        E: Clone + Ord,
+    {
+        let order = self.canonical_order();
+        let mut position = vec![0; order.len()];
+        for (i, n) in order.iter().enumerate() {
+            position[n.index()] = i;
+        }
+        let mut edges: Vec<_> = self
+            .raw_edges()
+            .iter()
+            .map(|e| {
+                let (a, b) = (position[e.source().index()], position[e.target().index()]);
+                (a, b, &e.weight)
+            })
+            .collect();
+        edges.sort();
+
+        let mut dag = Dag::with_capacity(order.len(), edges.len());
+        for &n in &order {
+            dag.add_node(self[n].clone());
+        }
+        for (a, b, w) in edges {
+            dag.graph
+                .add_edge(NodeIndex::new(a), NodeIndex::new(b), w.clone());
+        }
+        dag
+    }
+}
+
+fn invariants<N, E, Ix: IndexType>(dag: &Dag<N, E, Ix>) -> Vec<Invariant> {
+    let depths = dag.fold_down(|_, _| 0, |d, _, &p| d.max(p + 1));
+    let heights = dag.fold_up(|_, _| 0, |h, _, &c| h.max(c + 1));
+    (0..dag.node_count())
+        .map(|i| {
+            let n = NodeIndex::new(i);
+            let in_degree = dag.graph.edges_directed(n, pg::Incoming).count();
+            let out_degree = dag.graph.edges_directed(n, pg::Outgoing).count();
+            (depths[i], heights[i], in_degree, out_degree)
+        })
+        .collect()
+}
+
+// Whether every incoming edge of `u` corresponds to a distinct incoming edge of `v` from the image
+// of the same parent. All of `u`'s parents are expected to have been mapped.
+fn parents_match<N, E, Ix, N2, E2, Ix2, G>(
+    dag: &Dag<N, E, Ix>,
+    other: &Dag<N2, E2, Ix2>,
+    mapping: &[Option<NodeIndex<Ix2>>],
+    u: NodeIndex<Ix>,
+    v: NodeIndex<Ix2>,
+    edge_eq: &mut G,
+) -> bool
+where
+    Ix: IndexType,
+    Ix2: IndexType,
+    G: FnMut(&E, &E2) -> bool,
+{
+    let mut other_edges: Vec<_> = other
+        .graph
+        .edges_directed(v, pg::Incoming)
+        .map(|e| Some((e.source(), e.weight())))
+        .collect();
+    for edge in dag.graph.edges_directed(u, pg::Incoming) {
+        let parent = match mapping[edge.source().index()] {
+            Some(parent) => parent,
+            None => return false,
+        };
+        let found = other_edges.iter_mut().find(|slot| match slot {
+            Some((source, weight)) => *source == parent && edge_eq(edge.weight(), weight),
+            None => false,
+        });
+        match found {
+            Some(slot) => *slot = None,
+            None => return false,
+        }
+    }
+    true
+}
+
+// The rank of each value among all distinct values, in ascending order.
+fn ranks<'a, T: 'a + Ord>(values: impl Iterator<Item = &'a T>) -> Vec<usize> {
+    let values: Vec<_> = values.collect();
+    let mut sorted: Vec<_> = values.clone();
+    sorted.sort();
+    sorted.dedup();
+    values
+        .iter()
+        .map(|v| sorted.binary_search(v).expect("value was sorted"))
+        .collect()
+}
+
+// The colour of each node along with the (edge rank, colour) of each of its parents and children.
+type Signature = (usize, Vec<(usize, usize)>, Vec<(usize, usize)>);
+
+// Iteratively refine the given colours until no more nodes can be distinguished.
+fn refine<N, E, Ix: IndexType>(
+    dag: &Dag<N, E, Ix>,
+    edge_ranks: &[usize],
+    mut colors: Vec<usize>,
+) -> Vec<usize> {
+    let mut count = distinct(&colors);
+    loop {
+        let signatures: Vec<Signature> = (0..dag.node_count())
+            .map(|i| {
+                let n = NodeIndex::new(i);
+                let neighbors = |dir| {
+                    let mut list: Vec<_> = dag
+                        .graph
+                        .edges_directed(n, dir)
+                        .map(|e| {
+                            let other = if dir == pg::Incoming {
+                                e.source()
+                            } else {
+                                e.target()
+                            };
+                            (edge_ranks[e.id().index()], colors[other.index()])
+                        })
+                        .collect();
+                    list.sort_unstable();
+                    list
+                };
+                (colors[i], neighbors(pg::Incoming), neighbors(pg::Outgoing))
+            })
+            .collect();
+        colors = ranks(signatures.iter());
+        let new_count = distinct(&colors);
+        if new_count == count {
+            return colors;
+        }
+        count = new_count;
+    }
+}
+
+fn distinct(colors: &[usize]) -> usize {
+    colors.iter().max().map_or(0, |&max| max + 1)
+}
+
+// The sorted (edge rank, neighbour index) pairs of a node's incoming and outgoing edges.
+type Adjacency = (Vec<(usize, usize)>, Vec<(usize, usize)>);
+
+fn adjacency<N, E, Ix: IndexType>(
+    dag: &Dag<N, E, Ix>,
+    edge_ranks: &[usize],
+    n: NodeIndex<Ix>,
+) -> Adjacency {
+    let list = |dir| {
+        let mut list: Vec<_> = dag
+            .graph
+            .edges_directed(n, dir)
+            .map(|e| {
+                let other = if dir == pg::Incoming {
+                    e.source()
+                } else {
+                    e.target()
+                };
+                (edge_ranks[e.id().index()], other.index())
+            })
+            .collect();
+        list.sort_unstable();
+        list
+    };
+    (list(pg::Incoming), list(pg::Outgoing))
+}
+
+// A graph's encoding under some order: the weight rank of each node by position, followed by the
+// sorted edges as (source position, target position, edge rank).
+type Encoding = (Vec<usize>, Vec<(usize, usize, usize)>);
+
+// Search for the order with the smallest encoding, individualizing nodes to break ties.
+#[allow(clippy::type_complexity)]
+fn search<N, E, Ix: IndexType>(
+    dag: &Dag<N, E, Ix>,
+    node_ranks: &[usize],
+    edge_ranks: &[usize],
+    colors: Vec<usize>,
+    best: &mut Option<(Encoding, Vec<NodeIndex<Ix>>)>,
+) {
+    let n = colors.len();
+    // Find the first cell (by colour) with more than one node.
+    let mut cell_sizes = vec![0; distinct(&colors)];
+    for &c in &colors {
+        cell_sizes[c] += 1;
+    }
+    let cell = match cell_sizes.iter().position(|&size| size > 1) {
+        None => {
+            let mut order = vec![NodeIndex::new(0); n];
+            for (i, &c) in colors.iter().enumerate() {
+                order[c] = NodeIndex::new(i);
+            }
+            let encoding = encode(dag, node_ranks, edge_ranks, &colors);
+            let is_best = match best {
+                Some((b, _)) => encoding < *b,
+                None => true,
+            };
+            if is_best {
+                *best = Some((encoding, order));
+            }
+            return;
+        }
+        Some(cell) => cell,
+    };
+
+    let mut tried: Vec<Adjacency> = vec![];
+    for i in (0..n).filter(|&i| colors[i] == cell) {
+        // Skip twins of nodes already tried, as swapping twins is an automorphism.
+        let adj = adjacency(dag, edge_ranks, NodeIndex::new(i));
+        if tried.contains(&adj) {
+            continue;
+        }
+        tried.push(adj);
+        // Give the chosen node a colour that sorts before the rest of its cell.
+        let individualized = colors
+            .iter()
+            .enumerate()
+            .map(|(j, &c)| (c, j != i && c == cell))
+            .collect::<Vec<_>>();
+        let colors = refine(dag, edge_ranks, ranks(individualized.iter()));
+        search(dag, node_ranks, edge_ranks, colors, best);
+    }
+}
+
+fn encode<N, E, Ix: IndexType>(
+    dag: &Dag<N, E, Ix>,
+    node_ranks: &[usize],
+    edge_ranks: &[usize],
+    positions: &[usize],
+) -> Encoding {
+    let mut nodes = vec![0; positions.len()];
+    for (i, &rank) in node_ranks.iter().enumerate() {
+        nodes[positions[i]] = rank;
+    }
+    let mut edges: Vec<_> = dag
+        .raw_edges()
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let (a, b) = (positions[e.source().index()], positions[e.target().index()]);
+            (a, b, edge_ranks[i])
+        })
+        .collect();
+    edges.sort_unstable();
+    (nodes, edges)
+}
@@ -0,0 +1,415 @@
+//! Reading and writing **Dag**s in the [JSON Graph Format](https://jsongraphformat.info) (JGF).
+//!
+//! Node and edge weights are serialized as the `metadata` of their JGF nodes and edges. The JGF
+//! schema expects `metadata` to be an object, so weights are best represented by structs or maps.
+//! A weight that serializes as `null`, such as `()`, is written without any `metadata`, and any
+//! missing `metadata` is read as `null`.
+//!
+//! Only available with the `jgf` feature enabled.
+
+use crate::{Dag, NodeIndex};
+use petgraph::graph::{DefaultIx, IndexType};
+use serde::de::{DeserializeOwned, MapAccess, SeqAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// The JGF IDs of the nodes of a **Dag** read via [`Dag::from_jgf`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeIds {
+    ids: Vec<String>,
+    indices: HashMap<String, NodeIndex>,
+}
+
+/// An error produced while reading or writing JGF.
+#[derive(Debug)]
+pub enum JgfError {
+    /// The input is not valid JSON or does not match the structure of a JGF document.
+    Json(serde_json::Error),
+    /// The document contains neither a `graph` nor a `graphs` list containing a single graph.
+    MissingGraph,
+    /// The document contains the given number of graphs, rather than one, within its `graphs`
+    /// list or across both its `graph` and `graphs`.
+    MultipleGraphs(usize),
+    /// The graph, or one of its edges, is not `directed`.
+    Undirected,
+    /// More than one node has the given ID.
+    DuplicateNode(String),
+    /// An edge refers to a node ID that does not exist.
+    UnknownNode(String),
+    /// The weight of the node with the given ID could not be converted to or from its `metadata`.
+    InvalidNodeMetadata {
+        /// The ID of the node.
+        id: String,
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+    /// The weight of the edge at the given index could not be converted to or from its
+    /// `metadata`.
+    InvalidEdgeMetadata {
+        /// The index of the edge within the graph's `edges`.
+        index: usize,
+        /// The underlying error.
+        error: serde_json::Error,
+    },
+    /// The edge between the nodes with the given IDs would have created a cycle.
+    WouldCycle {
+        /// The ID of the edge's source node.
+        source: String,
+        /// The ID of the edge's target node.
+        target: String,
+    },
+}
+
+#[derive(Serialize)]
+struct DocumentRef<'a> {
+    graph: GraphRef<'a>,
+}
+
+#[derive(Serialize)]
+struct GraphRef<'a> {
+    directed: bool,
+    nodes: NodesRef<'a>,
+    edges: Vec<EdgeRef<'a>>,
+}
+
+// The nodes of a graph in index order, serialized as a map from ID to node.
+struct NodesRef<'a>(Vec<(&'a str, Value)>);
+
+#[derive(Serialize)]
+struct NodeRef<'a> {
+    #[serde(skip_serializing_if = "Value::is_null")]
+    metadata: &'a Value,
+}
+
+#[derive(Serialize)]
+struct EdgeRef<'a> {
+    source: &'a str,
+    target: &'a str,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    metadata: Value,
+}
+
+#[derive(Deserialize)]
+struct Document {
+    graph: Option<Graph>,
+    graphs: Option<Vec<Graph>>,
+}
+
+#[derive(Deserialize)]
+struct Graph {
+    #[serde(default = "directed")]
+    directed: bool,
+    #[serde(default)]
+    nodes: Nodes,
+    #[serde(default)]
+    edges: Vec<Edge>,
+}
+
+// The ID and metadata of each node in document order, read from either a map from ID to node
+// (JGF v2) or a list of nodes with `id` fields (JGF v1).
+#[derive(Default)]
+struct Nodes(Vec<(String, Value)>);
+
+#[derive(Deserialize)]
+struct Node {
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Deserialize)]
+struct NodeWithId {
+    id: String,
+    #[serde(default)]
+    metadata: Value,
+}
+
+#[derive(Deserialize)]
+struct Edge {
+    source: String,
+    target: String,
+    #[serde(default = "directed")]
+    directed: bool,
+    #[serde(default)]
+    metadata: Value,
+}
+
+impl NodeIds {
+    /// The JGF ID of the given node.
+    ///
+    /// **Panics** if the node does not exist.
+    pub fn id(&self, node: NodeIndex) -> &str {
+        &self.ids[node.index()]
+    }
+
+    /// The index of the node with the given JGF ID, if any.
+    pub fn index(&self, id: &str) -> Option<NodeIndex> {
+        self.indices.get(id).cloned()
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Whether there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// An iterator yielding the index and JGF ID of each node in order of index.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeIndex, &str)> {
+        self.ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (NodeIndex::new(i), id.as_str()))
+    }
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the `Dag` as a JGF document containing a single directed `graph`.
+    ///
+    /// `node_id` produces the JGF ID of each node, which must be unique. Nodes are written in
+    /// order of index and edges in order of index, with each weight serialized as `metadata`.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<(), u32>::new();
+    /// let a = dag.add_node(());
+    /// dag.add_child(a, 7, ());
+    ///
+    /// let json = dag.to_jgf(|n, _| format!("n{}", n.index())).unwrap();
+    /// assert_eq!(
+    ///     json,
+    ///     r#"{"graph":{"directed":true,"nodes":{"n0":{},"n1":{}},"edges":[{"source":"n0","target":"n1","metadata":7}]}}"#,
+    /// );
+    /// ```
+    pub fn to_jgf<F>(&self, mut node_id: F) -> Result<String, JgfError>
+    where
+        N: Serialize,
+        E: Serialize,
+        F: FnMut(NodeIndex<Ix>, &N) -> String,
+    {
+        let ids: Vec<String> = self
+            .graph
+            .raw_nodes()
+            .iter()
+            .enumerate()
+            .map(|(i, node)| node_id(NodeIndex::new(i), &node.weight))
+            .collect();
+        let mut seen = HashSet::with_capacity(ids.len());
+        if let Some(id) = ids.iter().find(|id| !seen.insert(id.as_str())) {
+            return Err(JgfError::DuplicateNode(id.clone()));
+        }
+
+        let mut nodes = Vec::with_capacity(ids.len());
+        for (id, node) in ids.iter().zip(self.graph.raw_nodes()) {
+            let metadata = serde_json::to_value(&node.weight).map_err(|error| {
+                JgfError::InvalidNodeMetadata {
+                    id: id.clone(),
+                    error,
+                }
+            })?;
+            nodes.push((id.as_str(), metadata));
+        }
+        let mut edges = Vec::with_capacity(self.edge_count());
+        for (index, edge) in self.graph.raw_edges().iter().enumerate() {
+            let metadata = serde_json::to_value(&edge.weight)
+                .map_err(|error| JgfError::InvalidEdgeMetadata { index, error })?;
+            edges.push(EdgeRef {
+                source: &ids[edge.source().index()],
+                target: &ids[edge.target().index()],
+                metadata,
+            });
+        }
+
+        let doc = DocumentRef {
+            graph: GraphRef {
+                directed: true,
+                nodes: NodesRef(nodes),
+                edges,
+            },
+        };
+        serde_json::to_string(&doc).map_err(JgfError::Json)
+    }
+}
+
+impl<N, E> Dag<N, E, DefaultIx> {
+    /// Read a JGF document into a `Dag`, along with the JGF ID of each node.
+    ///
+    /// The document must contain either a single `graph` or a `graphs` list of exactly one graph,
+    /// but not both, and neither the graph nor any of its edges may have `directed` set to `false`. Nodes may
+    /// be given either as a map from ID to node (JGF v2) or as a list of nodes with `id` fields
+    /// (JGF v1), and are added in document order. Each weight is deserialized from `metadata`.
+    /// Other fields, such as `label`, are ignored.
+    ///
+    /// Returns an error if the document is malformed, is undirected, contains duplicate node IDs
+    /// or edges between unknown nodes, or if any of its edges would create a cycle.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let json = r#"{
+    ///     "graph": {
+    ///         "directed": true,
+    ///         "nodes": { "fetch": { "metadata": "Fetch" }, "build": { "metadata": "Build" } },
+    ///         "edges": [{ "source": "fetch", "target": "build", "metadata": 2 }]
+    ///     }
+    /// }"#;
+    /// let (dag, ids) = Dag::<String, u32>::from_jgf(json).unwrap();
+    /// let build = ids.index("build").unwrap();
+    /// assert_eq!(dag[build], "Build");
+    /// assert_eq!(ids.id(build), "build");
+    /// ```
+    pub fn from_jgf(json: &str) -> Result<(Self, NodeIds), JgfError>
+    where
+        N: DeserializeOwned,
+        E: DeserializeOwned,
+    {
+        let doc: Document = serde_json::from_str(json).map_err(JgfError::Json)?;
+        let graph = match (doc.graph, doc.graphs) {
+            (Some(graph), None) => graph,
+            (Some(graph), Some(graphs)) => match graphs.len() {
+                0 => graph,
+                n => return Err(JgfError::MultipleGraphs(n + 1)),
+            },
+            (None, Some(mut graphs)) => match graphs.len() {
+                0 => return Err(JgfError::MissingGraph),
+                1 => graphs.pop().unwrap(),
+                n => return Err(JgfError::MultipleGraphs(n)),
+            },
+            (None, None) => return Err(JgfError::MissingGraph),
+        };
+        if !graph.directed || graph.edges.iter().any(|e| !e.directed) {
+            return Err(JgfError::Undirected);
+        }
+
+        let mut dag = Dag::with_capacity(graph.nodes.0.len(), graph.edges.len());
+        let mut ids = NodeIds::default();
+        for (id, metadata) in graph.nodes.0 {
+            if ids.indices.contains_key(&id) {
+                return Err(JgfError::DuplicateNode(id));
+            }
+            let weight = match serde_json::from_value(metadata) {
+                Ok(weight) => weight,
+                Err(error) => return Err(JgfError::InvalidNodeMetadata { id, error }),
+            };
+            ids.indices.insert(id.clone(), dag.add_node(weight));
+            ids.ids.push(id);
+        }
+        for (index, edge) in graph.edges.into_iter().enumerate() {
+            let node = |id: &String| match ids.index(id) {
+                Some(node) => Ok(node),
+                None => Err(JgfError::UnknownNode(id.clone())),
+            };
+            let (a, b) = (node(&edge.source)?, node(&edge.target)?);
+            let weight = serde_json::from_value(edge.metadata)
+                .map_err(|error| JgfError::InvalidEdgeMetadata { index, error })?;
+            if dag.add_edge(a, b, weight).is_err() {
+                return Err(JgfError::WouldCycle {
+                    source: edge.source,
+                    target: edge.target,
+                });
+            }
+        }
+        Ok((dag, ids))
+    }
+}
+
+// JGF graphs and edges are directed unless stated otherwise.
+fn directed() -> bool {
+    true
+}
+
+impl<'a> Serialize for NodesRef<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (id, metadata) in &self.0 {
+            map.serialize_entry(id, &NodeRef { metadata })?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Nodes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(NodesVisitor)
+    }
+}
+
+struct NodesVisitor;
+
+impl<'de> Visitor<'de> for NodesVisitor {
+    type Value = Nodes;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map from node IDs to nodes or a list of nodes")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Nodes, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut nodes = vec![];
+        while let Some((id, node)) = map.next_entry::<String, Node>()? {
+            nodes.push((id, node.metadata));
+        }
+        Ok(Nodes(nodes))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Nodes, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut nodes = vec![];
+        while let Some(node) = seq.next_element::<NodeWithId>()? {
+            nodes.push((node.id, node.metadata));
+        }
+        Ok(Nodes(nodes))
+    }
+}
+
+impl fmt::Display for JgfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JgfError::Json(err) => write!(f, "invalid JGF document: {}", err),
+            JgfError::MissingGraph => write!(f, "document does not contain a graph"),
+            JgfError::MultipleGraphs(n) => write!(f, "expected a single graph, found {}", n),
+            JgfError::Undirected => write!(f, "graph is undirected"),
+            JgfError::DuplicateNode(id) => write!(f, "duplicate node {:?}", id),
+            JgfError::UnknownNode(id) => write!(f, "edge refers to unknown node {:?}", id),
+            JgfError::InvalidNodeMetadata { id, error } => {
+                write!(f, "invalid metadata for node {:?}: {}", id, error)
+            }
+            JgfError::InvalidEdgeMetadata { index, error } => {
+                write!(f, "invalid metadata for edge {}: {}", index, error)
+            }
+            JgfError::WouldCycle { source, target } => {
+                write!(f, "edge {:?} -> {:?} would create a cycle", source, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JgfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JgfError::Json(error)
+            | JgfError::InvalidNodeMetadata { error, .. }
+            | JgfError::InvalidEdgeMetadata { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! This module includes the implementation of the **KeyedDag** data structure. The **KeyedDag**
+//! wraps a **Dag** and associates each of its nodes with a unique, user-provided key, allowing
+//! nodes to be looked up by key rather than by `NodeIndex`.
+
+use crate::{Dag, EdgeIndex, NodeIndex, WouldCycle};
+use petgraph::graph::{DefaultIx, IndexType};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A **Dag** whose nodes are each identified by a unique key of type **K**.
+///
+/// The **KeyedDag** maintains a mapping from keys to node indices alongside the inner **Dag**. All
+/// structural mutation goes through the **KeyedDag** so that this mapping always remains
+/// consistent with the graph, including when `remove_node` swaps the last node into the removed
+/// node's index.
+///
+/// Read-only access to the inner **Dag** (and in turn all of its walkers and petgraph trait
+/// implementations) is available via the [`dag`](KeyedDag::dag) method.
+#[derive(Clone, Debug)]
+pub struct KeyedDag<K, N, E, Ix: IndexType = DefaultIx> {
+    dag: Dag<N, E, Ix>,
+    indices: HashMap<K, NodeIndex<Ix>>,
+    // The key for each node, ordered by node index.
+    keys: Vec<K>,
+}
+
+impl<K, N, E, Ix> KeyedDag<K, N, E, Ix>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    /// Create a new, empty `KeyedDag`.
+    pub fn new() -> Self {
+        Self::with_capacity(1, 1)
+    }
+
+    /// Create a new `KeyedDag` with estimated capacity for its node and edge Vecs.
+    pub fn with_capacity(nodes: usize, edges: usize) -> Self {
+        KeyedDag {
+            dag: Dag::with_capacity(nodes, edges),
+            indices: HashMap::with_capacity(nodes),
+            keys: Vec::with_capacity(nodes),
+        }
+    }
+
+    /// Borrow the inner `Dag`.
+    ///
+    /// All existing indices may be used to index into this `Dag`.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        &self.dag
+    }
+
+    /// Take ownership of the `KeyedDag` and return the inner `Dag`, discarding the keys.
+    pub fn into_dag(self) -> Dag<N, E, Ix> {
+        self.dag
+    }
+
+    /// The total number of nodes in the **KeyedDag**.
+    pub fn node_count(&self) -> usize {
+        self.dag.node_count()
+    }
+
+    /// The total number of edges in the **KeyedDag**.
+    pub fn edge_count(&self) -> usize {
+        self.dag.edge_count()
+    }
+
+    /// Whether or not a node exists for the given key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        self.indices.contains_key(key)
+    }
+
+    /// The index of the node associated with the given key, if there is one.
+    pub fn node_index<Q>(&self, key: &Q) -> Option<NodeIndex<Ix>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        self.indices.get(key).copied()
+    }
+
+    /// The key associated with the node at the given index, if there is one.
+    pub fn key(&self, node: NodeIndex<Ix>) -> Option<&K> {
+        self.keys.get(node.index())
+    }
+
+    /// An iterator yielding all keys in the order of their node indices.
+    pub fn keys(&self) -> std::slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    /// Add a node with the given key and weight.
+    ///
+    /// If a node already exists for the key, its weight is replaced and its existing index is
+    /// returned.
+    ///
+    /// **Panics** if the Graph is at the maximum number of nodes for its index type.
+    pub fn add_node(&mut self, key: K, weight: N) -> NodeIndex<Ix> {
+        if let Some(&node) = self.indices.get(&key) {
+            self.dag[node] = weight;
+            return node;
+        }
+        let node = self.dag.add_node(weight);
+        self.indices.insert(key.clone(), node);
+        self.keys.push(key);
+        node
+    }
+
+    /// Add a new directed edge `a` -> `b` between the nodes with the given keys.
+    ///
+    /// If no node exists for either key, a node is created for it with a default weight.
+    ///
+    /// Checks if the edge would create a cycle in the graph in the same manner as
+    /// [`Dag::add_edge`]. If it would, the edge is not added, any nodes created by this call are
+    /// removed again and a `WouldCycle<E>` error with the given weight is returned.
+    ///
+    /// **Panics** if the Graph is at the maximum number of nodes or edges for its index type.
+    pub fn add_edge_by_key(&mut self, a: K, b: K, weight: E) -> Result<EdgeIndex<Ix>, WouldCycle<E>>
+    where
+        N: Default,
+    {
+        let a_exists = self.contains_key(&a);
+        let a_node = self.add_node_if_missing(a);
+        let b_exists = self.contains_key(&b);
+        let b_node = self.add_node_if_missing(b.clone());
+        match self.dag.add_edge(a_node, b_node, weight) {
+            Ok(edge) => Ok(edge),
+            Err(err) => {
+                // Remove in reverse order of creation so that no other indices are shifted.
+                if !b_exists {
+                    self.remove_node(&b);
+                }
+                if !a_exists {
+                    let a = self.keys[a_node.index()].clone();
+                    self.remove_node(&a);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Find and return the index to the edge that describes `a` -> `b` if there is one.
+    pub fn find_edge<Q>(&self, a: &Q, b: &Q) -> Option<EdgeIndex<Ix>>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        let a = self.node_index(a)?;
+        let b = self.node_index(b)?;
+        self.dag.find_edge(a, b)
+    }
+
+    /// Borrow the weight of the node with the given key.
+    pub fn node_weight<Q>(&self, key: &Q) -> Option<&N>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        self.node_index(key).and_then(|n| self.dag.node_weight(n))
+    }
+
+    /// Mutably borrow the weight of the node with the given key.
+    pub fn node_weight_mut<Q>(&mut self, key: &Q) -> Option<&mut N>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        let node = self.node_index(key)?;
+        self.dag.node_weight_mut(node)
+    }
+
+    /// Borrow the weight from the edge at the given index.
+    pub fn edge_weight(&self, edge: EdgeIndex<Ix>) -> Option<&E> {
+        self.dag.edge_weight(edge)
+    }
+
+    /// Mutably borrow the weight from the edge at the given index.
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex<Ix>) -> Option<&mut E> {
+        self.dag.edge_weight_mut(edge)
+    }
+
+    /// Remove the node with the given key along with all of its edges and return its weight.
+    ///
+    /// As with [`Dag::remove_node`], the last node is moved into the index of the removed node.
+    /// Its key remains associated with it at its new index.
+    pub fn remove_node<Q>(&mut self, key: &Q) -> Option<N>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq + Hash,
+    {
+        let node = self.indices.remove(key)?;
+        let weight = self.dag.remove_node(node);
+        self.keys.swap_remove(node.index());
+        if let Some(moved) = self.keys.get(node.index()) {
+            if let Some(index) = self.indices.get_mut::<K>(moved) {
+                *index = node;
+            }
+        }
+        weight
+    }
+
+    /// Remove an edge and return its weight, or `None` if it didn't exist.
+    pub fn remove_edge(&mut self, e: EdgeIndex<Ix>) -> Option<E> {
+        self.dag.remove_edge(e)
+    }
+
+    /// Removes all nodes, edges and keys from the **KeyedDag**.
+    pub fn clear(&mut self) {
+        self.dag.clear();
+        self.indices.clear();
+        self.keys.clear();
+    }
+
+    fn add_node_if_missing(&mut self, key: K) -> NodeIndex<Ix>
+    where
+        N: Default,
+    {
+        match self.indices.get(&key) {
+            Some(&node) => node,
+            None => self.add_node(key, N::default()),
+        }
+    }
+}
+
+impl<K, N, E, Ix> Default for KeyedDag<K, N, E, Ix>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        KeyedDag::new()
+    }
+}
+
+impl<K, N, E, Ix> From<KeyedDag<K, N, E, Ix>> for Dag<N, E, Ix>
+where
+    K: Clone + Eq + Hash,
+    Ix: IndexType,
+{
+    fn from(val: KeyedDag<K, N, E, Ix>) -> Self {
+        val.into_dag()
+    }
+}
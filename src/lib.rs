@@ -26,6 +26,12 @@
 //!
 //! # Allows the `Dag` to be serialized and deserialized.
 //! daggy = { version = "0.9.0", features = ["serde-1"] }
+//!
+//! # Enables reading and writing GraphML.
+//! daggy = { version = "0.9.0", features = ["graphml"] }
+//!
+//! # Enables reading and writing the JSON Graph Format.
+//! daggy = { version = "0.9.0", features = ["jgf"] }
 //! ```
 //!
 //! # Examples
@@ -98,12 +104,33 @@ use std::ops::{Index, IndexMut};
 
 // Petgraph re-exports.
 pub use petgraph::graph::{EdgeIndex, EdgeWeightsMut, NodeIndex, NodeWeightsMut};
-pub use petgraph::visit::Walker;
-
+pub use petgraph::visit::{Reversed, Walker};
+
+pub mod binary;
+pub mod dedup;
+pub mod depfile;
+pub mod diff;
+pub mod dot;
+pub mod edge_list;
+mod edit;
+#[cfg(feature = "graphml")]
+pub mod graphml;
+pub mod history;
+pub mod incremental;
+pub mod isomorphism;
+#[cfg(feature = "jgf")]
+pub mod jgf;
+pub mod keyed;
+pub mod merge;
+pub mod merkle;
+pub mod mermaid;
+pub mod observed;
+pub mod render;
 #[cfg(feature = "serde-1")]
-mod serde;
+pub mod serde;
 #[cfg(feature = "stable_dag")]
 pub mod stable_dag;
+pub mod transaction;
 pub mod walker;
 
 /// Read only access into a **Dag**'s internal node array.
@@ -573,6 +600,105 @@ where
         self.graph.clear_edges()
     }
 
+    /// Move the target of the edge at the given index to `new_target`, keeping the edge's weight
+    /// and index.
+    ///
+    /// Checks if the moved edge would create a cycle in the graph before making any changes. If
+    /// it would, the graph is left untouched and a `WouldCycle` error is returned containing the
+    /// existing path of nodes from `new_target` to the edge's source that the moved edge would
+    /// have closed into a cycle.
+    ///
+    /// Computes in **O(t + e')** time where "t" is the complexity of the cycle check and **e'** is
+    /// the number of edges connected to the affected nodes.
+    ///
+    /// **Panics** if there is no edge at the given index or if `new_target` does not exist.
+    pub fn retarget_edge(
+        &mut self,
+        e: EdgeIndex<Ix>,
+        new_target: NodeIndex<Ix>,
+    ) -> Result<(), WouldCycle<Vec<NodeIndex<Ix>>>> {
+        let (source, target) = self.edge_endpoints(e).expect("no edge for the given index");
+        assert!(
+            new_target.index() < self.node_count(),
+            "no node for `new_target`"
+        );
+        if target == new_target {
+            return Ok(());
+        }
+        if must_check_for_cycle(self, source, new_target) {
+            if let Some(path) = find_path(&self.graph, new_target, source) {
+                return Err(WouldCycle(path));
+            }
+        }
+        let weight = self
+            .graph
+            .remove_edge(e)
+            .expect("no edge for the given index");
+        self.add_edge_at(e, source, new_target, weight);
+        Ok(())
+    }
+
+    /// Move the source of the edge at the given index to `new_source`, keeping the edge's weight
+    /// and index.
+    ///
+    /// Checks if the moved edge would create a cycle in the graph before making any changes. If
+    /// it would, the graph is left untouched and a `WouldCycle` error is returned containing the
+    /// existing path of nodes from the edge's target to `new_source` that the moved edge would
+    /// have closed into a cycle.
+    ///
+    /// Computes in **O(t + e')** time where "t" is the complexity of the cycle check and **e'** is
+    /// the number of edges connected to the affected nodes.
+    ///
+    /// **Panics** if there is no edge at the given index or if `new_source` does not exist.
+    pub fn resource_edge(
+        &mut self,
+        e: EdgeIndex<Ix>,
+        new_source: NodeIndex<Ix>,
+    ) -> Result<(), WouldCycle<Vec<NodeIndex<Ix>>>> {
+        let (source, target) = self.edge_endpoints(e).expect("no edge for the given index");
+        assert!(
+            new_source.index() < self.node_count(),
+            "no node for `new_source`"
+        );
+        if source == new_source {
+            return Ok(());
+        }
+        if must_check_for_cycle(self, new_source, target) {
+            if let Some(path) = find_path(&self.graph, target, new_source) {
+                return Err(WouldCycle(path));
+            }
+        }
+        let weight = self
+            .graph
+            .remove_edge(e)
+            .expect("no edge for the given index");
+        self.add_edge_at(e, new_source, target, weight);
+        Ok(())
+    }
+
+    /// Add an edge without checking for cycles and move it to the given index.
+    ///
+    /// The edge that previously occupied `index` (if any) is moved to the end of the edge list,
+    /// exactly reversing the swap performed by `remove_edge`.
+    pub(crate) fn add_edge_at(
+        &mut self,
+        index: EdgeIndex<Ix>,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) {
+        let last = self.graph.add_edge(a, b, weight);
+        if index != last {
+            let (da, db) = self
+                .graph
+                .edge_endpoints(index)
+                .expect("index out of bounds");
+            // Removing the displaced edge swaps the newly added edge into its place.
+            let displaced = self.graph.remove_edge(index).expect("index out of bounds");
+            self.graph.add_edge(da, db, displaced);
+        }
+    }
+
     /// Add a new edge and parent node to the node at the given `NodeIndex`.
     ///
     /// Returns both the edge's `EdgeIndex` and the node's `NodeIndex`.
@@ -635,14 +761,14 @@ where
     }
 
     /// Read from the internal node array.
-    pub fn raw_nodes(&self) -> RawNodes<N, Ix> {
+    pub fn raw_nodes(&self) -> RawNodes<'_, N, Ix> {
         self.graph.raw_nodes()
     }
 
     /// An iterator yielding mutable access to all node weights.
     ///
     /// The order in which weights are yielded matches the order of their node indices.
-    pub fn node_weights_mut(&mut self) -> NodeWeightsMut<N, Ix> {
+    pub fn node_weights_mut(&mut self) -> NodeWeightsMut<'_, N, Ix> {
         self.graph.node_weights_mut()
     }
 
@@ -657,14 +783,14 @@ where
     }
 
     /// Read from the internal edge array.
-    pub fn raw_edges(&self) -> RawEdges<E, Ix> {
+    pub fn raw_edges(&self) -> RawEdges<'_, E, Ix> {
         self.graph.raw_edges()
     }
 
     /// An iterator yielding mutable access to all edge weights.
     ///
     /// The order in which weights are yielded matches the order of their edge indices.
-    pub fn edge_weights_mut(&mut self) -> EdgeWeightsMut<E, Ix> {
+    pub fn edge_weights_mut(&mut self) -> EdgeWeightsMut<'_, E, Ix> {
         self.graph.edge_weights_mut()
     }
 
@@ -693,10 +819,34 @@ where
     /// Remove the node at the given index from the `Dag` and return it if it exists.
     ///
     /// Note: Calling this may shift (and in turn invalidate) previously returned node indices!
+    /// Use [`remove_node_with_remap`](Dag::remove_node_with_remap) to find out which index moved.
     pub fn remove_node(&mut self, node: NodeIndex<Ix>) -> Option<N> {
         self.graph.remove_node(node)
     }
 
+    /// Remove the node at the given index from the `Dag` and return it if it exists, along with
+    /// the `(old_index, new_index)` of the node that was moved into its place.
+    ///
+    /// The last node in the `Dag` is always the one moved to fill the hole left by the removed
+    /// node, so no remapping is returned if the removed node was the last.
+    ///
+    /// **Note:** All edges connected to the removed node are removed too, which may shift the
+    /// indices of other edges in the same manner as [`remove_edge`](Dag::remove_edge).
+    #[allow(clippy::type_complexity)]
+    pub fn remove_node_with_remap(
+        &mut self,
+        node: NodeIndex<Ix>,
+    ) -> Option<(N, Option<(NodeIndex<Ix>, NodeIndex<Ix>)>)> {
+        let last = NodeIndex::new(self.node_count().checked_sub(1)?);
+        let weight = self.graph.remove_node(node)?;
+        let remap = if node != last {
+            Some((last, node))
+        } else {
+            None
+        };
+        Some((weight, remap))
+    }
+
     /// Remove an edge and return its weight, or `None` if it didn't exist.
     ///
     /// Computes in **O(e')** time, where **e'** is the size of four particular edge lists, for the
@@ -705,6 +855,103 @@ where
         self.graph.remove_edge(e)
     }
 
+    /// Remove an edge and return its weight if it exists, along with the `(old_index, new_index)`
+    /// of the edge that was moved into its place.
+    ///
+    /// The last edge in the `Dag` is always the one moved to fill the hole left by the removed
+    /// edge, so no remapping is returned if the removed edge was the last.
+    #[allow(clippy::type_complexity)]
+    pub fn remove_edge_with_remap(
+        &mut self,
+        e: EdgeIndex<Ix>,
+    ) -> Option<(E, Option<(EdgeIndex<Ix>, EdgeIndex<Ix>)>)> {
+        let last = EdgeIndex::new(self.edge_count().checked_sub(1)?);
+        let weight = self.graph.remove_edge(e)?;
+        let remap = if e != last { Some((last, e)) } else { None };
+        Some((weight, remap))
+    }
+
+    /// Keep all nodes for which the `visit` closure returns `true` and remove all others, along
+    /// with their edges.
+    ///
+    /// `visit` is provided a proxy reference to the `Dag`, so that the `Dag` may be walked and
+    /// its weights modified. Every node is visited before any node is removed.
+    ///
+    /// The remaining nodes and edges retain their relative order, however their indices are
+    /// compacted. Returns a table mapping each node's old index to its new index, or to `None` if
+    /// the node was removed.
+    ///
+    /// Removing nodes can never create a cycle, so no cycle checks are required.
+    ///
+    /// Computes in **O(n + e)** time.
+    pub fn retain_nodes<F>(&mut self, mut visit: F) -> Vec<Option<NodeIndex<Ix>>>
+    where
+        F: FnMut(pg::graph::Frozen<Self>, NodeIndex<Ix>) -> bool,
+    {
+        let keep: Vec<bool> = (0..self.node_count())
+            .map(|i| visit(pg::graph::Frozen::new(self), NodeIndex::new(i)))
+            .collect();
+        let kept = keep.iter().filter(|&&keep| keep).count();
+        let old_graph = std::mem::replace(&mut self.graph, DiGraph::with_capacity(0, 0));
+        let (nodes, edges) = old_graph.into_nodes_edges();
+        let mut graph = DiGraph::with_capacity(kept, edges.len());
+        let remap: Vec<_> = nodes
+            .into_iter()
+            .zip(keep)
+            .map(|(node, keep)| match keep {
+                true => Some(graph.add_node(node.weight)),
+                false => None,
+            })
+            .collect();
+        for edge in edges {
+            let a = remap[edge.source().index()];
+            let b = remap[edge.target().index()];
+            if let (Some(a), Some(b)) = (a, b) {
+                graph.add_edge(a, b, edge.weight);
+            }
+        }
+        self.graph = graph;
+        remap
+    }
+
+    /// Keep all edges for which the `visit` closure returns `true` and remove all others.
+    ///
+    /// `visit` is provided a proxy reference to the `Dag`, so that the `Dag` may be walked and
+    /// its weights modified. Every edge is visited before any edge is removed.
+    ///
+    /// All node indices remain unchanged. The remaining edges retain their relative order, however
+    /// their indices are compacted. Returns a table mapping each edge's old index to its new
+    /// index, or to `None` if the edge was removed.
+    ///
+    /// Removing edges can never create a cycle, so no cycle checks are required.
+    ///
+    /// Computes in **O(n + e)** time.
+    pub fn retain_edges<F>(&mut self, mut visit: F) -> Vec<Option<EdgeIndex<Ix>>>
+    where
+        F: FnMut(pg::graph::Frozen<Self>, EdgeIndex<Ix>) -> bool,
+    {
+        let keep: Vec<bool> = (0..self.edge_count())
+            .map(|i| visit(pg::graph::Frozen::new(self), EdgeIndex::new(i)))
+            .collect();
+        let kept = keep.iter().filter(|&&keep| keep).count();
+        let old_graph = std::mem::replace(&mut self.graph, DiGraph::with_capacity(0, 0));
+        let (nodes, edges) = old_graph.into_nodes_edges();
+        let mut graph = DiGraph::with_capacity(nodes.len(), kept);
+        for node in nodes {
+            graph.add_node(node.weight);
+        }
+        let remap = edges
+            .into_iter()
+            .zip(keep)
+            .map(|(edge, keep)| match keep {
+                true => Some(graph.add_edge(edge.source(), edge.target(), edge.weight)),
+                false => None,
+            })
+            .collect();
+        self.graph = graph;
+        remap
+    }
+
     /// A **Walker** type that may be used to step through the parents of the given child node.
     ///
     /// Unlike iterator types, **Walker**s do not require borrowing the internal **Graph**. This
@@ -788,6 +1035,141 @@ where
             self.transitive_reduce_iter(root, &mut Vec::new())
         }
     }
+
+    /// Reverse the direction of all edges in-place.
+    ///
+    /// Reversing the edges of a DAG can never create a cycle, so no cycle checks are required.
+    ///
+    /// All node and edge indices remain unchanged.
+    ///
+    /// Computes in **O(n + e)** time.
+    pub fn reverse(&mut self) {
+        self.graph.reverse();
+    }
+
+    /// Create a new `Dag` with the same nodes and edges as `self`, but with the direction of all
+    /// edges reversed.
+    ///
+    /// The resulting `Dag` has the same node and edge indices as `self`.
+    pub fn reversed(&self) -> Self
+    where
+        N: Clone,
+        E: Clone,
+    {
+        let mut dag = self.clone();
+        dag.reverse();
+        dag
+    }
+
+    /// A view of the `Dag` in which the direction of all edges is reversed.
+    ///
+    /// Unlike [`reversed`](Dag::reversed), this does not copy or mutate the `Dag`. The returned
+    /// [`Reversed`] adaptor implements the same petgraph visit traits as `&Dag`, so petgraph's
+    /// walkers and algorithms may be used on it as they are on the `Dag` itself.
+    pub fn as_reversed(&self) -> Reversed<&Self> {
+        Reversed(self)
+    }
+
+    /// Compute a value for every node from the values of its parents, visiting each node exactly
+    /// once in topological order.
+    ///
+    /// For each node, `init` produces the initial value from the node's index and weight. This
+    /// value is then folded together with each of the node's incoming edge weights and the
+    /// already computed value of the parent on the other end of the edge via `combine`.
+    ///
+    /// Returns the values indexed by node.
+    ///
+    /// Computes in **O(n + e)** time plus the cost of each `init` and `combine` call.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<&str, ()>::new();
+    /// let root = dag.add_node("root");
+    /// let (_, a) = dag.add_child(root, (), "a");
+    /// let (_, b) = dag.add_child(a, (), "b");
+    /// dag.add_edge(root, b, ()).unwrap();
+    ///
+    /// // The maximum depth of each node from a root.
+    /// let depths = dag.fold_down(|_, _| 0, |depth, _, &parent| depth.max(parent + 1));
+    /// assert_eq!(depths, vec![0, 1, 2]);
+    /// ```
+    pub fn fold_down<V, I, C>(&self, init: I, combine: C) -> Vec<V>
+    where
+        I: FnMut(NodeIndex<Ix>, &N) -> V,
+        C: FnMut(V, &E, &V) -> V,
+    {
+        self.fold(pg::Incoming, init, combine)
+    }
+
+    /// Compute a value for every node from the values of its children, visiting each node
+    /// exactly once in reverse topological order.
+    ///
+    /// For each node, `init` produces the initial value from the node's index and weight. This
+    /// value is then folded together with each of the node's outgoing edge weights and the
+    /// already computed value of the child on the other end of the edge via `combine`.
+    ///
+    /// Returns the values indexed by node.
+    ///
+    /// Computes in **O(n + e)** time plus the cost of each `init` and `combine` call.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<u32, ()>::new();
+    /// let root = dag.add_node(1);
+    /// let (_, a) = dag.add_child(root, (), 2);
+    /// dag.add_child(a, (), 3);
+    /// dag.add_child(root, (), 4);
+    ///
+    /// // The total weight of each node's subtree.
+    /// let totals = dag.fold_up(|_, &w| w, |total, _, &child| total + child);
+    /// assert_eq!(totals, vec![10, 5, 3, 4]);
+    /// ```
+    pub fn fold_up<V, I, C>(&self, init: I, combine: C) -> Vec<V>
+    where
+        I: FnMut(NodeIndex<Ix>, &N) -> V,
+        C: FnMut(V, &E, &V) -> V,
+    {
+        self.fold(pg::Outgoing, init, combine)
+    }
+
+    // Fold the values of the neighbors in the opposite direction to `dir` into each node.
+    fn fold<V, I, C>(&self, dir: pg::Direction, mut init: I, mut combine: C) -> Vec<V>
+    where
+        I: FnMut(NodeIndex<Ix>, &N) -> V,
+        C: FnMut(V, &E, &V) -> V,
+    {
+        use pg::visit::EdgeRef;
+        let mut order = self.topological_order();
+        if dir == pg::Outgoing {
+            order.reverse();
+        }
+        let mut values: Vec<Option<V>> = (0..self.node_count()).map(|_| None).collect();
+        for node in order {
+            let mut value = init(node, &self.graph[node]);
+            for edge in self.graph.edges_directed(node, dir) {
+                let other = match dir {
+                    pg::Incoming => edge.source(),
+                    pg::Outgoing => edge.target(),
+                };
+                let other_value = values[other.index()]
+                    .as_ref()
+                    .expect("visited out of order");
+                value = combine(value, edge.weight(), other_value);
+            }
+            values[node.index()] = Some(value);
+        }
+        values
+            .into_iter()
+            .map(|v| v.expect("node not visited"))
+            .collect()
+    }
+
+    /// All node indices in topological order.
+    pub(crate) fn topological_order(&self) -> Vec<NodeIndex<Ix>> {
+        pg::algo::toposort(&self.graph, None).expect("a Dag never contains a cycle")
+    }
 }
 
 /// After adding a new edge to the graph, we use this function immediately after to check whether
@@ -807,6 +1189,32 @@ where
         && dag.find_edge(a, b).is_none()
 }
 
+/// Find a path of nodes leading from `from` to `to` (inclusive of both) if there is one.
+pub(crate) fn find_path<G>(graph: G, from: G::NodeId, to: G::NodeId) -> Option<Vec<G::NodeId>>
+where
+    G: IntoNeighbors + Visitable,
+{
+    use pg::visit::VisitMap;
+    let mut discovered = graph.visit_map();
+    discovered.visit(from);
+    let mut stack = vec![(from, graph.neighbors(from))];
+    loop {
+        let next = match stack.last_mut() {
+            None => return None,
+            Some((node, _)) if *node == to => break,
+            Some((_, neighbors)) => neighbors.next(),
+        };
+        match next {
+            Some(node) if discovered.visit(node) => stack.push((node, graph.neighbors(node))),
+            Some(_) => (),
+            None => {
+                stack.pop();
+            }
+        }
+    }
+    Some(stack.into_iter().map(|(node, _)| node).collect())
+}
+
 // Dag implementations.
 
 impl<N, E, Ix> From<Dag<N, E, Ix>> for DiGraph<N, E, Ix>
@@ -0,0 +1,232 @@
+//! Three-way merging of **Dag**s that were concurrently derived from a common base.
+
+use crate::diff::{diff, DagDiff, PatchError};
+use crate::{find_path, Dag, NodeIndex};
+use petgraph::graph::IndexType;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A conflict between the changes made by each side of a [`merge`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum MergeConflict<K> {
+    /// Both sides changed the weight of the node, to different values.
+    BothModified(K),
+    /// Both sides added a node with the key, with different weights.
+    BothAdded(K),
+    /// One side changed the weight of the node while the other removed it.
+    ModifiedAndRemoved(K),
+    /// One side removed the node while the other added an edge to or from it.
+    RemovedAndConnected(K),
+    /// Both sides changed the weight of the edge between the given nodes, to different values.
+    EdgeBothModified(K, K),
+    /// Both sides added an edge between the given nodes, with different weights.
+    EdgeBothAdded(K, K),
+    /// One side changed the weight of the edge between the given nodes while the other removed
+    /// it.
+    EdgeModifiedAndRemoved(K, K),
+    /// The changes of each side are acyclic on their own, but together would form a cycle
+    /// through the given nodes.
+    Cycle(Vec<K>),
+    /// The changes of each side do not conflict directly, but together could not be applied to
+    /// the base.
+    Inapplicable(PatchError<K>),
+}
+
+/// Combine the changes made from `base` to `ours` and from `base` to `theirs` into a new **Dag**.
+///
+/// Nodes are matched across all three **Dag**s by the key returned by `node_key`, and edges by the
+/// keys of their endpoints along with their position among any parallel edges (see [`diff`]).
+/// Changes made identically by both sides are applied once.
+///
+/// Returns all conflicting changes if the two sides cannot be merged. If the sides do not
+/// conflict directly but their combined edges would form a cycle, a single
+/// [`MergeConflict::Cycle`] is returned describing one such cycle.
+///
+/// **Panics** if two nodes within the same **Dag** share a key.
+pub fn merge<N, E, Ix, K, F>(
+    base: &Dag<N, E, Ix>,
+    ours: &Dag<N, E, Ix>,
+    theirs: &Dag<N, E, Ix>,
+    mut node_key: F,
+) -> Result<Dag<N, E, Ix>, Vec<MergeConflict<K>>>
+where
+    N: Clone + PartialEq,
+    E: Clone + PartialEq,
+    Ix: IndexType,
+    K: Clone + Eq + Hash,
+    F: FnMut(&N) -> K,
+{
+    let ours = diff(base, ours, &mut node_key, |a, b| a == b);
+    let theirs = diff(base, theirs, &mut node_key, |a, b| a == b);
+    let (combined, conflicts) = combine(&ours, &theirs);
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+
+    // Apply everything but the added edges, none of which can introduce a cycle.
+    let mut dag = base.clone();
+    let structural = DagDiff {
+        added_edges: vec![],
+        ..combined
+    };
+    if let Err(err) = dag.apply(&structural, &mut node_key, |a, b| a == b) {
+        return Err(vec![MergeConflict::Inapplicable(err)]);
+    }
+
+    // Add the edges one at a time in order to find a cycle if there is one.
+    let indices: HashMap<K, NodeIndex<Ix>> = dag
+        .raw_nodes()
+        .iter()
+        .enumerate()
+        .map(|(i, n)| (node_key(&n.weight), NodeIndex::new(i)))
+        .collect();
+    for edge in combined.added_edges {
+        let a = indices[&edge.source];
+        let b = indices[&edge.target];
+        if dag.add_edge(a, b, edge.weight).is_err() {
+            let path = find_path(dag.graph(), b, a).expect("no path for cycle");
+            let keys = path.into_iter().map(|n| node_key(&dag[n])).collect();
+            return Err(vec![MergeConflict::Cycle(keys)]);
+        }
+    }
+    Ok(dag)
+}
+
+// Combine two diffs from the same base into one, along with any conflicts between them.
+fn combine<K, N, E>(
+    ours: &DagDiff<K, N, E>,
+    theirs: &DagDiff<K, N, E>,
+) -> (DagDiff<K, N, E>, Vec<MergeConflict<K>>)
+where
+    K: Clone + Eq + Hash,
+    N: Clone + PartialEq,
+    E: Clone + PartialEq,
+{
+    let mut conflicts = vec![];
+    let mut combined = DagDiff {
+        added_nodes: vec![],
+        removed_nodes: vec![],
+        modified_nodes: vec![],
+        added_edges: vec![],
+        removed_edges: vec![],
+        modified_edges: vec![],
+    };
+
+    // Nodes.
+    for (a, b) in &[(ours, theirs), (theirs, ours)] {
+        let first = std::ptr::eq(*a, ours);
+        let b_removed: HashMap<_, _> = b.removed_nodes.iter().map(|(k, n)| (k, n)).collect();
+        let b_modified: HashMap<_, _> = b.modified_nodes.iter().map(|c| (&c.key, c)).collect();
+        let b_added: HashMap<_, _> = b.added_nodes.iter().map(|(k, n)| (k, n)).collect();
+        for change in &a.modified_nodes {
+            if b_removed.contains_key(&change.key) {
+                if first {
+                    conflicts.push(MergeConflict::ModifiedAndRemoved(change.key.clone()));
+                }
+            } else if let Some(other) = b_modified.get(&change.key) {
+                if first && other.new != change.new {
+                    conflicts.push(MergeConflict::BothModified(change.key.clone()));
+                } else if first {
+                    combined.modified_nodes.push(change.clone());
+                }
+            } else {
+                combined.modified_nodes.push(change.clone());
+            }
+        }
+        for (key, weight) in &a.removed_nodes {
+            if b_modified.contains_key(key) {
+                if first {
+                    conflicts.push(MergeConflict::ModifiedAndRemoved(key.clone()));
+                }
+                continue;
+            }
+            let connected = b
+                .added_edges
+                .iter()
+                .any(|e| e.source == *key || e.target == *key);
+            if connected {
+                conflicts.push(MergeConflict::RemovedAndConnected(key.clone()));
+            } else if first || !b_removed.contains_key(key) {
+                combined.removed_nodes.push((key.clone(), weight.clone()));
+            }
+        }
+        for (key, weight) in &a.added_nodes {
+            match b_added.get(key) {
+                Some(&other) if other != weight => {
+                    if first {
+                        conflicts.push(MergeConflict::BothAdded(key.clone()));
+                    }
+                }
+                Some(_) if !first => (),
+                _ => combined.added_nodes.push((key.clone(), weight.clone())),
+            }
+        }
+    }
+
+    // Edges.
+    for (a, b) in &[(ours, theirs), (theirs, ours)] {
+        let first = std::ptr::eq(*a, ours);
+        for change in &a.modified_edges {
+            let same = |s: &K, t: &K, o: usize| {
+                *s == change.source && *t == change.target && o == change.occurrence
+            };
+            let pair = || (change.source.clone(), change.target.clone());
+            if b.removed_edges
+                .iter()
+                .any(|e| same(&e.source, &e.target, e.occurrence))
+            {
+                if first {
+                    let (s, t) = pair();
+                    conflicts.push(MergeConflict::EdgeModifiedAndRemoved(s, t));
+                }
+            } else if let Some(other) = b
+                .modified_edges
+                .iter()
+                .find(|e| same(&e.source, &e.target, e.occurrence))
+            {
+                if first && other.new != change.new {
+                    let (s, t) = pair();
+                    conflicts.push(MergeConflict::EdgeBothModified(s, t));
+                } else if first {
+                    combined.modified_edges.push(change.clone());
+                }
+            } else {
+                combined.modified_edges.push(change.clone());
+            }
+        }
+        for removed in &a.removed_edges {
+            let modified = b.modified_edges.iter().any(|e| {
+                e.source == removed.source
+                    && e.target == removed.target
+                    && e.occurrence == removed.occurrence
+            });
+            if modified {
+                if first {
+                    let (s, t) = (removed.source.clone(), removed.target.clone());
+                    conflicts.push(MergeConflict::EdgeModifiedAndRemoved(s, t));
+                }
+            } else if first || !b.removed_edges.contains(removed) {
+                combined.removed_edges.push(removed.clone());
+            }
+        }
+        for added in &a.added_edges {
+            let other = b.added_edges.iter().find(|e| {
+                e.source == added.source
+                    && e.target == added.target
+                    && e.occurrence == added.occurrence
+            });
+            match other {
+                Some(other) if other.weight != added.weight => {
+                    if first {
+                        let (s, t) = (added.source.clone(), added.target.clone());
+                        conflicts.push(MergeConflict::EdgeBothAdded(s, t));
+                    }
+                }
+                Some(_) if !first => (),
+                _ => combined.added_edges.push(added.clone()),
+            }
+        }
+    }
+
+    (combined, conflicts)
+}
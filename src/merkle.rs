@@ -0,0 +1,105 @@
+//! Merkle hashing of a **Dag** for content addressing.
+//!
+//! The merkle hash of a node covers its own weight along with the weights and merkle hashes of all
+//! of its outgoing edges and children, and in turn everything that the node (transitively)
+//! depends on. Hashes are independent of node indices, so two **Dag**s with the same structure
+//! and weights produce the same hashes regardless of the order in which they were built.
+
+use crate::Dag;
+use petgraph as pg;
+use petgraph::graph::IndexType;
+use petgraph::visit::EdgeRef;
+
+/// Describes whether the order of a node's children contributes to its merkle hash.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ChildOrder {
+    /// Children are sorted by the hash of their edge before being hashed into their parent, so
+    /// the order in which edges were added does not affect the result.
+    Unordered,
+    /// Children are hashed into their parent in order of their edge indices, i.e. the order in
+    /// which the edges were added (assuming no edges have since been removed).
+    Ordered,
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Compute the merkle hash of every node, visiting each node once in reverse topological
+    /// order.
+    ///
+    /// For each outgoing edge of a node, `edge_hash` combines the edge's weight with the merkle
+    /// hash of the child it points to. `node_hash` then combines the node's weight with the
+    /// resulting edge hashes, ordered according to `order`, to produce the node's merkle hash.
+    ///
+    /// Returns the hashes indexed by node.
+    ///
+    /// Computes in **O(n + e log e)** time plus the cost of hashing.
+    pub fn merkle_hashes<H, F, G>(
+        &self,
+        order: ChildOrder,
+        mut node_hash: F,
+        mut edge_hash: G,
+    ) -> Vec<H>
+    where
+        H: Clone + Ord,
+        F: FnMut(&N, &[H]) -> H,
+        G: FnMut(&E, &H) -> H,
+    {
+        let mut hashes: Vec<Option<H>> = (0..self.node_count()).map(|_| None).collect();
+        let mut edges = vec![];
+        let mut entries = vec![];
+        for node in self.topological_order().into_iter().rev() {
+            edges.clear();
+            edges.extend(self.graph.edges_directed(node, pg::Outgoing));
+            if order == ChildOrder::Ordered {
+                edges.sort_by_key(|e| e.id());
+            }
+            entries.clear();
+            for edge in &edges {
+                let child = hashes[edge.target().index()].as_ref();
+                entries.push(edge_hash(
+                    edge.weight(),
+                    child.expect("visited out of order"),
+                ));
+            }
+            if order == ChildOrder::Unordered {
+                entries.sort();
+            }
+            hashes[node.index()] = Some(node_hash(&self.graph[node], &entries));
+        }
+        hashes
+            .into_iter()
+            .map(|h| h.expect("node not visited"))
+            .collect()
+    }
+
+    /// Compute a digest of the whole `Dag` from the merkle hashes of its nodes.
+    ///
+    /// The merkle hashes of all root nodes (those without parents) are computed as in
+    /// [`merkle_hashes`](Dag::merkle_hashes), sorted, and passed to `digest`. As every node is
+    /// reachable from at least one root, the result covers the entire `Dag` and is independent
+    /// of node and edge indices.
+    pub fn merkle_digest<H, F, G, D>(
+        &self,
+        order: ChildOrder,
+        node_hash: F,
+        edge_hash: G,
+        digest: D,
+    ) -> H
+    where
+        H: Clone + Ord,
+        F: FnMut(&N, &[H]) -> H,
+        G: FnMut(&E, &H) -> H,
+        D: FnOnce(&[H]) -> H,
+    {
+        let hashes = self.merkle_hashes(order, node_hash, edge_hash);
+        let mut roots: Vec<H> = self
+            .graph
+            .externals(pg::Incoming)
+            .map(|n| hashes[n.index()].clone())
+            .collect();
+        roots.sort();
+        digest(&roots)
+    }
+}
@@ -0,0 +1,350 @@
+//! Writing **Dag**s as Mermaid flowcharts, along with a parser for the same subset of Mermaid.
+//!
+//! Nodes are given the IDs `n0`, `n1`, etc. by index, so IDs never clash with Mermaid keywords,
+//! and all labels are quoted with any special characters escaped as Mermaid entity codes.
+
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph::graph::{DefaultIx, IndexType};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// An error produced while parsing a Mermaid flowchart via [`Dag::from_mermaid`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FromMermaidError {
+    /// The input is not a flowchart in the supported subset of Mermaid.
+    Syntax {
+        /// The line on which the error occurred, starting from 1.
+        line: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// The edge between the nodes with the given IDs would have created a cycle.
+    WouldCycle {
+        /// The ID of the edge's source node.
+        source: String,
+        /// The ID of the edge's target node.
+        target: String,
+    },
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Write the `Dag` as a Mermaid `flowchart TD` block.
+    ///
+    /// `label` produces the text of each node and `edge_label` the text of each edge, where an
+    /// empty edge label is omitted.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<&str, &str>::new();
+    /// let root = dag.add_node("root");
+    /// dag.add_child(root, "uses", "a \"b\"");
+    ///
+    /// let mermaid = dag.to_mermaid(|_, n| n.to_string(), |_, e| e.to_string());
+    /// assert_eq!(
+    ///     mermaid,
+    ///     "flowchart TD\n    n0[\"root\"]\n    n1[\"a #quot;b#quot;\"]\n    n0 -->|\"uses\"| n1\n",
+    /// );
+    /// ```
+    pub fn to_mermaid<F, G>(&self, label: F, edge_label: G) -> String
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> String,
+        G: FnMut(EdgeIndex<Ix>, &E) -> String,
+    {
+        self.to_mermaid_grouped(|_, _| None, label, edge_label)
+    }
+
+    /// Write the `Dag` as a Mermaid `flowchart TD` block, clustering nodes into subgraphs.
+    ///
+    /// Nodes for which `group` returns the same key are written within a subgraph titled with
+    /// that key. Subgraphs are written in the order in which their keys first appear, and nodes
+    /// for which `group` returns `None` are written outside of any subgraph.
+    ///
+    /// See [`Dag::to_mermaid`].
+    pub fn to_mermaid_grouped<H, F, G>(
+        &self,
+        mut group: H,
+        mut label: F,
+        mut edge_label: G,
+    ) -> String
+    where
+        H: FnMut(NodeIndex<Ix>, &N) -> Option<String>,
+        F: FnMut(NodeIndex<Ix>, &N) -> String,
+        G: FnMut(EdgeIndex<Ix>, &E) -> String,
+    {
+        let mut ungrouped = vec![];
+        let mut groups: Vec<(String, Vec<usize>)> = vec![];
+        let mut group_indices = HashMap::new();
+        for (i, node) in self.graph.raw_nodes().iter().enumerate() {
+            match group(NodeIndex::new(i), &node.weight) {
+                None => ungrouped.push(i),
+                Some(key) => {
+                    let g = *group_indices.entry(key.clone()).or_insert_with(|| {
+                        groups.push((key, vec![]));
+                        groups.len() - 1
+                    });
+                    groups[g].1.push(i);
+                }
+            }
+        }
+
+        let mut out = String::from("flowchart TD\n");
+        let mut write_node = |out: &mut String, indent: &str, i: usize| {
+            let text = label(NodeIndex::new(i), &self.graph.raw_nodes()[i].weight);
+            write!(out, "{}n{}[", indent, i).unwrap();
+            write_label(out, &text);
+            out.push_str("]\n");
+        };
+        for &i in &ungrouped {
+            write_node(&mut out, "    ", i);
+        }
+        for (g, (key, nodes)) in groups.iter().enumerate() {
+            write!(out, "    subgraph g{}[", g).unwrap();
+            write_label(&mut out, key);
+            out.push_str("]\n");
+            for &i in nodes {
+                write_node(&mut out, "        ", i);
+            }
+            out.push_str("    end\n");
+        }
+        for (i, edge) in self.graph.raw_edges().iter().enumerate() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            let text = edge_label(EdgeIndex::new(i), &edge.weight);
+            write!(out, "    n{} -->", a).unwrap();
+            if !text.is_empty() {
+                out.push('|');
+                write_label(&mut out, &text);
+                out.push('|');
+            }
+            writeln!(out, " n{}", b).unwrap();
+        }
+        out
+    }
+}
+
+impl Dag<String, String, DefaultIx> {
+    /// Parse a Mermaid flowchart into a `Dag`.
+    ///
+    /// Supports the subset of Mermaid written by [`Dag::to_mermaid`]: a `flowchart` or `graph`
+    /// header, node declarations of the form `id` or `id["label"]`, edges of the form `a --> b`
+    /// or `a -->|"label"| b`, `subgraph` blocks and `%%` comments. Labels may also be unquoted.
+    ///
+    /// Nodes are added in the order in which their IDs first appear. The weight of each node is
+    /// its label, or its ID if it is never given one, and the weight of each edge is its label,
+    /// or an empty string if it has none. Subgraphs are flattened.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let dag = Dag::from_mermaid("flowchart TD\n    a[\"A\"] --> b\n    b -->|\"x\"| c\n").unwrap();
+    /// assert_eq!(dag.node_count(), 3);
+    /// assert_eq!(dag.raw_nodes()[0].weight, "A");
+    /// assert_eq!(dag.raw_edges()[1].weight, "x");
+    /// ```
+    pub fn from_mermaid(mermaid: &str) -> Result<Self, FromMermaidError> {
+        let mut dag = Dag::new();
+        let mut nodes: HashMap<String, NodeIndex> = HashMap::new();
+        // The line of each `subgraph` that has not yet been closed by an `end`.
+        let mut subgraphs = vec![];
+        let mut header = false;
+        for (i, line) in mermaid.lines().enumerate() {
+            let line_number = i + 1;
+            let error = |message: &str| FromMermaidError::Syntax {
+                line: line_number,
+                message: message.to_string(),
+            };
+            let line = line.trim().trim_end_matches(';').trim_end();
+            if line.is_empty() || line.starts_with("%%") {
+                continue;
+            }
+            let mut cursor = Cursor {
+                rest: line,
+                edge_label: None,
+            };
+            if !header {
+                let keyword = cursor.id();
+                if keyword != "flowchart" && keyword != "graph" {
+                    return Err(error("expected `flowchart`"));
+                }
+                header = true;
+                continue;
+            }
+            if line == "end" {
+                if subgraphs.pop().is_none() {
+                    return Err(error("unexpected `end`"));
+                }
+                continue;
+            }
+            if line.starts_with("subgraph ") || line == "subgraph" {
+                subgraphs.push(line_number);
+                continue;
+            }
+
+            // A chain of nodes joined by edges, e.g. `a["A"] --> b -->|"x"| c`.
+            let mut prev: Option<(String, NodeIndex)> = None;
+            loop {
+                let id = cursor.id();
+                if id.is_empty() {
+                    return Err(error("expected a node ID"));
+                }
+                let node = match nodes.get(id) {
+                    Some(&node) => node,
+                    None => {
+                        let node = dag.add_node(id.to_string());
+                        nodes.insert(id.to_string(), node);
+                        node
+                    }
+                };
+                if cursor.eat("[") {
+                    let text = cursor.label(']').map_err(&error)?;
+                    dag[node] = text;
+                }
+                if let Some((source, a)) = prev.take() {
+                    let weight = cursor.edge_label.take().unwrap_or_default();
+                    if dag.add_edge(a, node, weight).is_err() {
+                        return Err(FromMermaidError::WouldCycle {
+                            source,
+                            target: id.to_string(),
+                        });
+                    }
+                }
+                if cursor.rest.is_empty() {
+                    break;
+                }
+                if !cursor.eat("-->") {
+                    return Err(error("expected `-->`"));
+                }
+                if cursor.eat("|") {
+                    let text = cursor.label('|').map_err(&error)?;
+                    cursor.edge_label = Some(text);
+                }
+                prev = Some((id.to_string(), node));
+            }
+        }
+        if !header {
+            return Err(FromMermaidError::Syntax {
+                line: 1,
+                message: "expected `flowchart`".to_string(),
+            });
+        }
+        if let Some(&line) = subgraphs.last() {
+            return Err(FromMermaidError::Syntax {
+                line,
+                message: "expected `end` to close `subgraph`".to_string(),
+            });
+        }
+        Ok(dag)
+    }
+}
+
+// Write the text as a quoted label, replacing special characters with Mermaid entity codes.
+fn write_label(out: &mut String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '#' => out.push_str("#35;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '|' => out.push_str("#124;"),
+            '\n' => out.push_str("<br>"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+// Reverse the escaping performed by `write_label`.
+fn unescape(text: &str) -> String {
+    const CODES: &[(&str, &str)] = &[
+        ("#quot;", "\""),
+        ("#35;", "#"),
+        ("#lt;", "<"),
+        ("#gt;", ">"),
+        ("#124;", "|"),
+        ("<br>", "\n"),
+    ];
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    'outer: while let Some(c) = rest.chars().next() {
+        for &(code, replacement) in CODES {
+            if let Some(after) = rest.strip_prefix(code) {
+                out.push_str(replacement);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+// The remaining text of a line being parsed, along with any edge label awaiting its target.
+struct Cursor<'a> {
+    rest: &'a str,
+    edge_label: Option<String>,
+}
+
+impl<'a> Cursor<'a> {
+    fn eat(&mut self, s: &str) -> bool {
+        match self.rest.strip_prefix(s) {
+            Some(rest) => {
+                self.rest = rest.trim_start();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // A possibly empty ID made up of alphanumerics, `_` and `-` (where not the start of `-->`).
+    fn id(&mut self) -> &'a str {
+        let mut end = 0;
+        for (i, c) in self.rest.char_indices() {
+            if c.is_alphanumeric() || c == '_' || (c == '-' && !self.rest[i..].starts_with("-->")) {
+                end = i + c.len_utf8();
+            } else {
+                break;
+            }
+        }
+        let (id, rest) = self.rest.split_at(end);
+        self.rest = rest.trim_start();
+        id
+    }
+
+    // A quoted or unquoted label followed by the `close` delimiter.
+    fn label(&mut self, close: char) -> Result<String, &'static str> {
+        let text = if let Some(quoted) = self.rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or("unterminated label")?;
+            let text = &quoted[..end];
+            self.rest = quoted[end + 1..].trim_start();
+            if !self.rest.starts_with(close) {
+                return Err("unexpected text after label");
+            }
+            text
+        } else {
+            let end = self.rest.find(close).ok_or("unterminated label")?;
+            let text = self.rest[..end].trim();
+            self.rest = &self.rest[end..];
+            text
+        };
+        self.rest = self.rest[close.len_utf8()..].trim_start();
+        Ok(unescape(text))
+    }
+}
+
+impl std::fmt::Display for FromMermaidError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FromMermaidError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            FromMermaidError::WouldCycle { source, target } => {
+                write!(f, "edge {:?} -> {:?} would create a cycle", source, target)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FromMermaidError {}
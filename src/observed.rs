@@ -0,0 +1,324 @@
+//! This module includes the implementation of the **ObservedDag** data structure. The
+//! **ObservedDag** wraps a **Dag** and notifies a **DagObserver** of every mutation, allowing
+//! derived data (layouts, caches, search indices) to be kept in sync with the graph.
+
+use crate::{Dag, EdgeIndex, EdgeIndices, NodeIndex, WouldCycle};
+use petgraph as pg;
+use petgraph::graph::{DefaultIx, IndexType};
+use petgraph::IntoWeightedEdge;
+
+/// Callbacks invoked by an **ObservedDag** after each mutation.
+///
+/// All methods have empty default implementations, so that implementors need only override the
+/// callbacks they care about.
+#[allow(unused_variables)]
+pub trait DagObserver<N, E, Ix: IndexType = DefaultIx> {
+    /// A node was added at the given index.
+    fn on_node_added(&mut self, node: NodeIndex<Ix>, weight: &N) {}
+
+    /// An edge `source` -> `target` was added at the given index.
+    fn on_edge_added(
+        &mut self,
+        edge: EdgeIndex<Ix>,
+        source: NodeIndex<Ix>,
+        target: NodeIndex<Ix>,
+        weight: &E,
+    ) {
+    }
+
+    /// The edge `source` -> `target` at the given index was removed.
+    ///
+    /// If `swapped_from` is `Some`, the edge previously at that index was moved into the index of
+    /// the removed edge.
+    fn on_edge_removed(
+        &mut self,
+        edge: EdgeIndex<Ix>,
+        source: NodeIndex<Ix>,
+        target: NodeIndex<Ix>,
+        weight: &E,
+        swapped_from: Option<EdgeIndex<Ix>>,
+    ) {
+    }
+
+    /// The node at the given index was removed.
+    ///
+    /// All of the node's edges are removed (and reported via `on_edge_removed`) beforehand.
+    ///
+    /// If `swapped_from` is `Some`, the node previously at that index was moved into the index of
+    /// the removed node.
+    fn on_node_removed(
+        &mut self,
+        node: NodeIndex<Ix>,
+        weight: &N,
+        swapped_from: Option<NodeIndex<Ix>>,
+    ) {
+    }
+
+    /// The weight of the node at the given index was replaced or modified.
+    fn on_node_weight_changed(&mut self, node: NodeIndex<Ix>, weight: &N) {}
+
+    /// The weight of the edge at the given index was replaced or modified.
+    fn on_edge_weight_changed(&mut self, edge: EdgeIndex<Ix>, weight: &E) {}
+
+    /// All nodes and edges were removed.
+    fn on_cleared(&mut self) {}
+}
+
+/// An observer that ignores all notifications.
+impl<N, E, Ix> DagObserver<N, E, Ix> for () where Ix: IndexType {}
+
+/// A **Dag** that notifies an observer of type **O** of each mutation.
+///
+/// Only mutations made via the **ObservedDag** are reported, so mutable access to the inner
+/// **Dag** is not provided. Read-only access is available via [`dag`](ObservedDag::dag).
+#[derive(Clone, Debug)]
+pub struct ObservedDag<N, E, O, Ix: IndexType = DefaultIx> {
+    dag: Dag<N, E, Ix>,
+    observer: O,
+}
+
+impl<N, E, O, Ix> ObservedDag<N, E, O, Ix>
+where
+    O: DagObserver<N, E, Ix>,
+    Ix: IndexType,
+{
+    /// Create a new, empty `ObservedDag` that notifies the given observer.
+    pub fn new(observer: O) -> Self {
+        Self::from_dag(Dag::new(), observer)
+    }
+
+    /// Begin observing the given `Dag`.
+    ///
+    /// The observer is not notified of the `Dag`'s existing nodes and edges.
+    pub fn from_dag(dag: Dag<N, E, Ix>, observer: O) -> Self {
+        ObservedDag { dag, observer }
+    }
+
+    /// Create an `ObservedDag` from an iterator yielding edges, notifying the observer of every
+    /// node and edge added.
+    ///
+    /// See [`Dag::from_edges`].
+    pub fn from_edges<I>(edges: I, observer: O) -> Result<Self, WouldCycle<E>>
+    where
+        I: IntoIterator,
+        I::Item: IntoWeightedEdge<E>,
+        <I::Item as IntoWeightedEdge<E>>::NodeId: Into<NodeIndex<Ix>>,
+        N: Default,
+    {
+        let mut observed = Self::new(observer);
+        observed.extend_with_edges(edges)?;
+        Ok(observed)
+    }
+
+    /// Borrow the inner `Dag`.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        &self.dag
+    }
+
+    /// Borrow the observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Mutably borrow the observer.
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Take ownership of the `ObservedDag` and return the inner `Dag` and the observer.
+    pub fn into_parts(self) -> (Dag<N, E, Ix>, O) {
+        (self.dag, self.observer)
+    }
+
+    /// Add a new node with the given weight.
+    ///
+    /// See [`Dag::add_node`].
+    pub fn add_node(&mut self, weight: N) -> NodeIndex<Ix> {
+        let node = self.dag.add_node(weight);
+        self.observer.on_node_added(node, &self.dag[node]);
+        node
+    }
+
+    /// Add a new directed edge `a` -> `b` with the given weight.
+    ///
+    /// See [`Dag::add_edge`]. The observer is not notified if the edge would create a cycle.
+    pub fn add_edge(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle<E>> {
+        let edge = self.dag.add_edge(a, b, weight)?;
+        self.notify_edge_added(edge);
+        Ok(edge)
+    }
+
+    /// Add the given directed edges all at once.
+    ///
+    /// See [`Dag::add_edges`]. The observer is notified of each edge once all have been added, or
+    /// not at all if the edges would create a cycle.
+    pub fn add_edges<I>(&mut self, edges: I) -> Result<EdgeIndices<Ix>, WouldCycle<Vec<E>>>
+    where
+        I: IntoIterator<Item = (NodeIndex<Ix>, NodeIndex<Ix>, E)>,
+    {
+        let indices = self.dag.add_edges(edges)?;
+        for i in indices.indices.clone() {
+            self.notify_edge_added(EdgeIndex::new(i));
+        }
+        Ok(indices)
+    }
+
+    /// Extend the `Dag` with the given edges, inserting nodes as necessary.
+    ///
+    /// See [`Dag::extend_with_edges`]. If an edge would cause a cycle, the observer is still
+    /// notified of the nodes and edges that were added before it.
+    pub fn extend_with_edges<I>(&mut self, edges: I) -> Result<(), WouldCycle<E>>
+    where
+        I: IntoIterator,
+        I::Item: IntoWeightedEdge<E>,
+        <I::Item as IntoWeightedEdge<E>>::NodeId: Into<NodeIndex<Ix>>,
+        N: Default,
+    {
+        let (node_count, edge_count) = (self.dag.node_count(), self.dag.edge_count());
+        let result = self.dag.extend_with_edges(edges);
+        for i in node_count..self.dag.node_count() {
+            let node = NodeIndex::new(i);
+            self.observer.on_node_added(node, &self.dag[node]);
+        }
+        for i in edge_count..self.dag.edge_count() {
+            self.notify_edge_added(EdgeIndex::new(i));
+        }
+        result
+    }
+
+    /// Update the edge `a` -> `b` with the given weight, adding it if it doesn't already exist.
+    ///
+    /// See [`Dag::update_edge`].
+    pub fn update_edge(
+        &mut self,
+        a: NodeIndex<Ix>,
+        b: NodeIndex<Ix>,
+        weight: E,
+    ) -> Result<EdgeIndex<Ix>, WouldCycle<E>> {
+        match self.dag.find_edge(a, b) {
+            Some(edge) => {
+                self.set_edge_weight(edge, weight);
+                Ok(edge)
+            }
+            None => self.add_edge(a, b, weight),
+        }
+    }
+
+    /// Add a new edge and child node to the node at the given index.
+    ///
+    /// See [`Dag::add_child`].
+    pub fn add_child(
+        &mut self,
+        parent: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let (edge, node) = self.dag.add_child(parent, edge, node);
+        self.observer.on_node_added(node, &self.dag[node]);
+        self.notify_edge_added(edge);
+        (edge, node)
+    }
+
+    /// Add a new edge and parent node to the node at the given index.
+    ///
+    /// See [`Dag::add_parent`].
+    pub fn add_parent(
+        &mut self,
+        child: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let (edge, node) = self.dag.add_parent(child, edge, node);
+        self.observer.on_node_added(node, &self.dag[node]);
+        self.notify_edge_added(edge);
+        (edge, node)
+    }
+
+    /// Remove the node at the given index along with all of its edges.
+    ///
+    /// The observer is notified of the removal of each edge before that of the node. See
+    /// [`Dag::remove_node`].
+    pub fn remove_node(&mut self, node: NodeIndex<Ix>) -> Option<N> {
+        self.dag.node_weight(node)?;
+        for &dir in &[pg::Outgoing, pg::Incoming] {
+            while let Some(edge) = self.dag.graph.first_edge(node, dir) {
+                self.remove_edge(edge);
+            }
+        }
+        let (weight, remap) = self.dag.remove_node_with_remap(node)?;
+        let swapped_from = remap.map(|(old, _)| old);
+        self.observer.on_node_removed(node, &weight, swapped_from);
+        Some(weight)
+    }
+
+    /// Remove the edge at the given index and return its weight.
+    ///
+    /// See [`Dag::remove_edge`].
+    pub fn remove_edge(&mut self, edge: EdgeIndex<Ix>) -> Option<E> {
+        let (source, target) = self.dag.edge_endpoints(edge)?;
+        let (weight, remap) = self.dag.remove_edge_with_remap(edge)?;
+        let swapped_from = remap.map(|(old, _)| old);
+        self.observer
+            .on_edge_removed(edge, source, target, &weight, swapped_from);
+        Some(weight)
+    }
+
+    /// Replace the weight of the node at the given index, returning the old weight.
+    pub fn set_node_weight(&mut self, node: NodeIndex<Ix>, weight: N) -> Option<N> {
+        self.modify_node_weight(node, |w| std::mem::replace(w, weight))
+    }
+
+    /// Replace the weight of the edge at the given index, returning the old weight.
+    pub fn set_edge_weight(&mut self, edge: EdgeIndex<Ix>, weight: E) -> Option<E> {
+        self.modify_edge_weight(edge, |w| std::mem::replace(w, weight))
+    }
+
+    /// Modify the weight of the node at the given index with the given function, notifying the
+    /// observer once it returns.
+    pub fn modify_node_weight<F, R>(&mut self, node: NodeIndex<Ix>, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut N) -> R,
+    {
+        let result = f(self.dag.node_weight_mut(node)?);
+        self.observer.on_node_weight_changed(node, &self.dag[node]);
+        Some(result)
+    }
+
+    /// Modify the weight of the edge at the given index with the given function, notifying the
+    /// observer once it returns.
+    pub fn modify_edge_weight<F, R>(&mut self, edge: EdgeIndex<Ix>, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut E) -> R,
+    {
+        let result = f(self.dag.edge_weight_mut(edge)?);
+        self.observer.on_edge_weight_changed(edge, &self.dag[edge]);
+        Some(result)
+    }
+
+    /// Removes all nodes and edges.
+    pub fn clear(&mut self) {
+        self.dag.clear();
+        self.observer.on_cleared();
+    }
+
+    fn notify_edge_added(&mut self, edge: EdgeIndex<Ix>) {
+        let (source, target) = self.dag.edge_endpoints(edge).expect("no edge for index");
+        self.observer
+            .on_edge_added(edge, source, target, &self.dag[edge]);
+    }
+}
+
+impl<N, E, O, Ix> Default for ObservedDag<N, E, O, Ix>
+where
+    O: DagObserver<N, E, Ix> + Default,
+    Ix: IndexType,
+{
+    fn default() -> Self {
+        ObservedDag::new(O::default())
+    }
+}
@@ -0,0 +1,271 @@
+//! Rendering **Dag**s as text diagrams for terminals, logs and test failure messages.
+//!
+//! Diagrams are drawn in the style of `git log --graph`, with one node per row and each edge
+//! occupying a vertical lane from its parent down to its child:
+//!
+//! ```text
+//! *    fetch
+//! |-.
+//! * |  build
+//! | *  lint
+//! |-'
+//! *    publish
+//! ```
+//!
+//! A `*` marks each node, `|` continues a lane, `.` begins a lane for an additional child of the
+//! node above and `'` ends a lane by joining it with the lane of the node below. Where a
+//! horizontal connection crosses another lane, the lane is drawn over it.
+
+use crate::{Dag, NodeIndex};
+use petgraph::graph::IndexType;
+use std::fmt;
+
+/// A text diagram of a **Dag** produced by [`Dag::ascii_diagram`].
+///
+/// Both the `Display` and `Debug` implementations write the diagram verbatim, so that a failing
+/// `assert_eq!` between a diagram and an expected diagram created via `From<&str>` shows both as
+/// they would be drawn.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct AsciiDiagram(String);
+
+impl AsciiDiagram {
+    /// The diagram as a string, with each row terminated by a newline.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Take ownership of the diagram as a string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Render the `Dag` as a text diagram in the style of `git log --graph`.
+    ///
+    /// Nodes are drawn one per row in order of their topological level, i.e. the length of the
+    /// longest path from a root, and then by index. `label` produces the text of each node, which
+    /// is drawn to the right of the lanes and may span multiple lines. Parallel edges are drawn
+    /// as a single lane. Trailing whitespace is trimmed from every row.
+    ///
+    /// See the [`render`](crate::render) module for the meaning of each character.
+    ///
+    /// ```rust
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<&str, ()>::new();
+    /// let a = dag.add_node("a");
+    /// let (_, b) = dag.add_child(a, (), "b");
+    /// let (_, c) = dag.add_child(a, (), "c");
+    /// let (_, d) = dag.add_child(b, (), "d");
+    /// dag.add_edge(c, d, ()).unwrap();
+    ///
+    /// let diagram = dag.render_ascii(|_, n| n.to_string());
+    /// assert_eq!(diagram, "*    a\n|-.\n* |  b\n| *  c\n|-'\n*    d\n");
+    /// ```
+    pub fn render_ascii<F>(&self, mut label: F) -> String
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> String,
+    {
+        let depths = self.fold_down(|_, _| 0, |depth, _, &parent| depth.max(parent + 1));
+        let mut order: Vec<usize> = (0..self.node_count()).collect();
+        order.sort_by_key(|&i| depths[i]);
+        let mut position = vec![0; order.len()];
+        for (p, &i) in order.iter().enumerate() {
+            position[i] = p;
+        }
+
+        // The child awaited by each lane, or `None` if the lane is free.
+        let mut lanes: Vec<Option<usize>> = vec![];
+        // Each row's lanes along with the node's label, if the row is part of one.
+        let mut rows: Vec<(String, Option<String>)> = vec![];
+        for &node in &order {
+            let incoming: Vec<usize> = (0..lanes.len())
+                .filter(|&i| lanes[i] == Some(node))
+                .collect();
+            let col = match incoming.first() {
+                Some(&col) => col,
+                None => free_lane(&mut lanes),
+            };
+            if incoming.len() > 1 {
+                let active: Vec<bool> = lanes.iter().map(Option::is_some).collect();
+                rows.push((connector(&active, col, &incoming[1..], '\''), None));
+                for &i in &incoming[1..] {
+                    lanes[i] = None;
+                }
+            }
+            lanes[col] = Some(node);
+
+            let mut children: Vec<usize> = self
+                .graph
+                .neighbors(NodeIndex::new(node))
+                .map(|n| n.index())
+                .collect();
+            children.sort_by_key(|&child| position[child]);
+            children.dedup();
+
+            let text = label(NodeIndex::new(node), &self.graph[NodeIndex::new(node)]);
+            let mut lines = text.split('\n');
+            let first = lines.next().unwrap_or_default().to_string();
+            rows.push((lane_row(&lanes, col, '*'), Some(first)));
+            let below = if children.is_empty() { ' ' } else { '|' };
+            for line in lines {
+                rows.push((lane_row(&lanes, col, below), Some(line.to_string())));
+            }
+
+            lanes[col] = children.first().cloned();
+            let forks: Vec<usize> = children
+                .iter()
+                .skip(1)
+                .map(|&child| {
+                    let lane = free_lane(&mut lanes);
+                    lanes[lane] = Some(child);
+                    lane
+                })
+                .collect();
+            if !forks.is_empty() {
+                let active: Vec<bool> = lanes.iter().map(Option::is_some).collect();
+                rows.push((connector(&active, col, &forks, '.'), None));
+            }
+            while lanes.last() == Some(&None) {
+                lanes.pop();
+            }
+        }
+
+        let width = rows
+            .iter()
+            .filter(|(_, text)| text.is_some())
+            .map(|(graph, _)| graph.trim_end().len())
+            .max()
+            .unwrap_or(0);
+        let mut out = String::new();
+        for (graph, text) in rows {
+            let row = match text {
+                Some(text) => format!("{:width$}  {}", graph, text, width = width),
+                None => graph,
+            };
+            out.push_str(row.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render the `Dag` as an [`AsciiDiagram`], which displays as the diagram itself.
+    ///
+    /// This is useful for comparing the structure of a `Dag` with an expected diagram in tests,
+    /// as any mismatch is shown as a pair of readable diagrams.
+    ///
+    /// See [`Dag::render_ascii`].
+    ///
+    /// ```rust
+    /// use daggy::render::AsciiDiagram;
+    /// use daggy::Dag;
+    ///
+    /// let mut dag = Dag::<&str, ()>::new();
+    /// let root = dag.add_node("root");
+    /// dag.add_child(root, (), "leaf");
+    ///
+    /// let diagram = dag.ascii_diagram(|_, n| n.to_string());
+    /// assert_eq!(diagram, AsciiDiagram::from("*  root\n*  leaf\n"));
+    /// ```
+    pub fn ascii_diagram<F>(&self, label: F) -> AsciiDiagram
+    where
+        F: FnMut(NodeIndex<Ix>, &N) -> String,
+    {
+        AsciiDiagram(self.render_ascii(label))
+    }
+}
+
+// The index of the leftmost free lane, adding a new lane if all are in use.
+fn free_lane(lanes: &mut Vec<Option<usize>>) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(lane) => lane,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+// A row with `node` drawn in lane `col` and every other lane that is in use drawn with `|`.
+fn lane_row(lanes: &[Option<usize>], col: usize, node: char) -> String {
+    let mut row = String::new();
+    for (i, lane) in lanes.iter().enumerate() {
+        if i > 0 {
+            row.push(' ');
+        }
+        row.push(match lane {
+            _ if i == col => node,
+            Some(_) => '|',
+            None => ' ',
+        });
+    }
+    row
+}
+
+// A row joining each of the `corners` lanes to lane `col` with a horizontal line.
+fn connector(active: &[bool], col: usize, corners: &[usize], corner: char) -> String {
+    let lo = corners.iter().cloned().fold(col, Ord::min);
+    let hi = corners.iter().cloned().fold(col, Ord::max);
+    let mut row = String::new();
+    for (i, &active) in active.iter().enumerate() {
+        if i > 0 {
+            row.push(if lo < i && i <= hi { '-' } else { ' ' });
+        }
+        row.push(match i {
+            _ if i == col => '|',
+            _ if corners.contains(&i) => corner,
+            _ if active => '|',
+            _ if lo < i && i < hi => '-',
+            _ => ' ',
+        });
+    }
+    row
+}
+
+impl<'a> From<&'a str> for AsciiDiagram {
+    fn from(diagram: &'a str) -> Self {
+        AsciiDiagram(diagram.to_string())
+    }
+}
+
+impl From<String> for AsciiDiagram {
+    fn from(diagram: String) -> Self {
+        AsciiDiagram(diagram)
+    }
+}
+
+impl PartialEq<str> for AsciiDiagram {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for AsciiDiagram {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for AsciiDiagram {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl fmt::Display for AsciiDiagram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for AsciiDiagram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Begin on a new line so that the first row lines up with the rest.
+        writeln!(f)?;
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,340 @@
+//! A human-friendly serde representation of a **Dag** as a map from node IDs to their weights and
+//! children.
+//!
+//! Intended for use via `#[serde(with = "daggy::serde::adjacency")]`:
+//!
+//! ```rust
+//! use daggy::Dag;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Pipeline {
+//!     #[serde(with = "daggy::serde::adjacency")]
+//!     stages: Dag<String, u32>,
+//! }
+//!
+//! let json = r#"{
+//!     "stages": {
+//!         "fetch": { "weight": "Fetch", "children": [{ "id": "build", "weight": 2 }] },
+//!         "build": { "weight": "Build", "children": [{ "id": "test", "weight": 1 }] },
+//!         "test": { "weight": "Test" }
+//!     }
+//! }"#;
+//! let pipeline: Pipeline = serde_json::from_str(json).unwrap();
+//! assert_eq!(pipeline.stages.node_count(), 3);
+//! assert_eq!(pipeline.stages.edge_count(), 2);
+//! ```
+//!
+//! Each node is an entry in the map, keyed by its ID, with an optional `weight` field and an
+//! optional `children` list. Each child is either an object with the child's `id` and an optional
+//! edge `weight`, or just the child's ID. A missing node or edge weight is deserialized from `()`,
+//! so may only be omitted for weight types such as `()` or `Option<T>`. Any other field is an
+//! error, so that a misspelt field is not silently ignored.
+//!
+//! Nodes are added to the **Dag** in the order in which they appear, and edges in the order of
+//! their parents and then their position within each `children` list. Deserialization fails with
+//! an error naming the offending IDs if a node appears twice, if a child ID does not refer to a
+//! node, or if the edges contain a cycle.
+//!
+//! When serializing, each node's ID is its index, so node indices are preserved by a round trip.
+//! Edge indices are only preserved if the edges were added in order of their parents' indices.
+//!
+//! The [`stable`] submodule provides the same representation for a **StableDag**.
+
+use crate::{find_path, Dag, NodeIndex};
+use petgraph::algo::DfsSpace;
+use petgraph::graph::IndexType;
+use serde::de::value::UnitDeserializer;
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Serialize)]
+struct NodeRef<'a, N, E> {
+    weight: &'a N,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<ChildRef<'a, E>>,
+}
+
+#[derive(Serialize)]
+struct ChildRef<'a, E> {
+    id: String,
+    weight: &'a E,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeEntry<N, E> {
+    #[serde(default = "Option::default")]
+    weight: Option<N>,
+    #[serde(default = "Vec::new")]
+    children: Vec<ChildEntry<E>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChildEntry<E> {
+    Id(String),
+    Object(ChildObject<E>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChildObject<E> {
+    id: String,
+    #[serde(default = "Option::default")]
+    weight: Option<E>,
+}
+
+/// Serialize the `Dag` as a map from each node's index to its weight and children.
+pub fn serialize<N, E, Ix, S>(dag: &Dag<N, E, Ix>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    N: Serialize,
+    E: Serialize,
+    Ix: IndexType,
+    S: Serializer,
+{
+    let nodes = dag
+        .raw_nodes()
+        .iter()
+        .map(|node| Some(&node.weight))
+        .collect();
+    let edges = dag
+        .raw_edges()
+        .iter()
+        .map(|edge| (edge.source().index(), edge.target().index(), &edge.weight));
+    serialize_entries(nodes, edges, serializer)
+}
+
+// Serialize each node in `nodes` under its index, with `null` for a vacant index, and with the
+// edges `(parent, child, weight)` as its children.
+fn serialize_entries<'a, N, E, I, S>(
+    nodes: Vec<Option<&'a N>>,
+    edges: I,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    N: Serialize,
+    E: Serialize + 'a,
+    I: Iterator<Item = (usize, usize, &'a E)>,
+    S: Serializer,
+{
+    let mut children: Vec<Vec<ChildRef<E>>> = nodes.iter().map(|_| vec![]).collect();
+    for (parent, child, weight) in edges {
+        let id = child.to_string();
+        children[parent].push(ChildRef { id, weight });
+    }
+    let mut map = serializer.serialize_map(Some(nodes.len()))?;
+    for (i, (weight, children)) in nodes.into_iter().zip(children).enumerate() {
+        let entry = weight.map(|weight| NodeRef { weight, children });
+        map.serialize_entry(&i.to_string(), &entry)?;
+    }
+    map.end()
+}
+
+/// Deserialize a `Dag` from a map from node IDs to their weights and children.
+///
+/// See the [module documentation](self) for the expected format.
+pub fn deserialize<'de, N, E, Ix, D>(deserializer: D) -> Result<Dag<N, E, Ix>, D::Error>
+where
+    N: Deserialize<'de>,
+    E: Deserialize<'de>,
+    Ix: IndexType,
+    D: Deserializer<'de>,
+{
+    let dag = deserializer.deserialize_map(AdjacencyVisitor::new(false))?;
+    // Without vacant entries, every node weight is `Some`.
+    let graph = dag.graph.filter_map_owned(|_, n| n, |_, e| Some(e));
+    Ok(Dag {
+        graph,
+        cycle_state: DfsSpace::default(),
+    })
+}
+
+// Reads the map into a `Dag` with a `None` weight for each `null` entry, which are only accepted if
+// `vacant` is set.
+struct AdjacencyVisitor<N, E, Ix> {
+    vacant: bool,
+    _types: PhantomData<(N, E, Ix)>,
+}
+
+impl<N, E, Ix> AdjacencyVisitor<N, E, Ix> {
+    fn new(vacant: bool) -> Self {
+        AdjacencyVisitor {
+            vacant,
+            _types: PhantomData,
+        }
+    }
+}
+
+impl<'de, N, E, Ix> Visitor<'de> for AdjacencyVisitor<N, E, Ix>
+where
+    N: Deserialize<'de>,
+    E: Deserialize<'de>,
+    Ix: IndexType,
+{
+    type Value = Dag<Option<N>, E, Ix>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map from node IDs to their weights and children")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut dag = Dag::new();
+        let mut ids: Vec<String> = vec![];
+        // A vacant entry's ID maps to `None`, so that it cannot be named as a child.
+        let mut indices: HashMap<String, Option<NodeIndex<Ix>>> = HashMap::new();
+        let mut children = vec![];
+        while let Some(id) = map.next_key::<String>()? {
+            if indices.contains_key(&id) {
+                return Err(de::Error::custom(format!("duplicate node {:?}", id)));
+            }
+            let entry: Option<NodeEntry<N, E>> = if self.vacant {
+                map.next_value()
+            } else {
+                map.next_value().map(Some)
+            }
+            .map_err(|e| de::Error::custom(format!("node {:?}: {}", id, e)))?;
+            let (weight, entry_children) = match entry {
+                Some(NodeEntry {
+                    weight: Some(weight),
+                    children,
+                }) => (Some(weight), children),
+                Some(NodeEntry {
+                    weight: None,
+                    children,
+                }) => (Some(unit_weight(&id, "node")?), children),
+                None => (None, vec![]),
+            };
+            let n = dag.add_node(weight);
+            indices.insert(id.clone(), dag[n].as_ref().map(|_| n));
+            ids.push(id);
+            children.push(entry_children);
+        }
+
+        for (parent, children) in children.into_iter().enumerate() {
+            let a = NodeIndex::new(parent);
+            for child in children {
+                let (id, weight) = match child {
+                    ChildEntry::Object(ChildObject {
+                        id,
+                        weight: Some(weight),
+                    }) => (id, weight),
+                    ChildEntry::Object(ChildObject { id, weight: None }) | ChildEntry::Id(id) => {
+                        let weight = unit_weight(&id, "edge")?;
+                        (id, weight)
+                    }
+                };
+                let b = match indices.get(&id) {
+                    Some(&Some(b)) => b,
+                    Some(None) | None => {
+                        let msg = format!("node {:?} has unknown child {:?}", ids[parent], id);
+                        return Err(de::Error::custom(msg));
+                    }
+                };
+                if dag.add_edge(a, b, weight).is_err() {
+                    let path = find_path(dag.graph(), b, a).unwrap_or_default();
+                    let cycle: Vec<_> = std::iter::once(a)
+                        .chain(path)
+                        .map(|n| format!("{:?}", ids[n.index()]))
+                        .collect();
+                    let msg = format!("edges contain a cycle: {}", cycle.join(" -> "));
+                    return Err(de::Error::custom(msg));
+                }
+            }
+        }
+        Ok(dag)
+    }
+}
+
+// Deserialize an omitted weight from `()`.
+fn unit_weight<'de, T, Err>(id: &str, kind: &str) -> Result<T, Err>
+where
+    T: Deserialize<'de>,
+    Err: de::Error,
+{
+    T::deserialize(UnitDeserializer::<Err>::new())
+        .map_err(|e| Err::custom(format!("{} weight for {:?} is missing: {}", kind, id, e)))
+}
+
+/// The same representation for a **StableDag**, in which each vacant node index is an entry with a
+/// `null` value.
+///
+/// Intended for use via `#[serde(with = "daggy::serde::adjacency::stable")]`:
+///
+/// ```rust
+/// use daggy::stable_dag::StableDag;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Pipeline {
+///     #[serde(with = "daggy::serde::adjacency::stable")]
+///     stages: StableDag<String, ()>,
+/// }
+///
+/// let json = r#"{
+///     "stages": {
+///         "0": { "weight": "Fetch", "children": ["2"] },
+///         "1": null,
+///         "2": { "weight": "Test" }
+///     }
+/// }"#;
+/// let pipeline: Pipeline = serde_json::from_str(json).unwrap();
+/// assert_eq!(pipeline.stages.node_count(), 2);
+/// assert_eq!(pipeline.stages[daggy::NodeIndex::new(2)], "Test");
+/// ```
+///
+/// The vacant indices below the highest occupied index are preserved by a round trip, so the
+/// indices of all nodes are too. A `null` entry cannot be named as a child.
+#[cfg(feature = "stable_dag")]
+pub mod stable {
+    use super::AdjacencyVisitor;
+    use crate::stable_dag::StableDag;
+    use crate::NodeIndex;
+    use petgraph::graph::IndexType;
+    use petgraph::visit::{EdgeRef, IntoEdgeReferences, NodeIndexable};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serialize the `StableDag` as a map from each node index to its weight and children, or to
+    /// `null` if the index is vacant.
+    pub fn serialize<N, E, Ix, S>(
+        dag: &StableDag<N, E, Ix>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        N: Serialize,
+        E: Serialize,
+        Ix: IndexType,
+        S: Serializer,
+    {
+        let graph = dag.graph();
+        let nodes = (0..graph.node_bound())
+            .map(|i| graph.node_weight(NodeIndex::new(i)))
+            .collect();
+        let edges = graph
+            .edge_references()
+            .map(|edge| (edge.source().index(), edge.target().index(), edge.weight()));
+        super::serialize_entries(nodes, edges, serializer)
+    }
+
+    /// Deserialize a `StableDag` from a map from node IDs to their weights and children, or to
+    /// `null` for a vacant index.
+    ///
+    /// See the [module documentation](super) for the expected format.
+    pub fn deserialize<'de, N, E, Ix, D>(deserializer: D) -> Result<StableDag<N, E, Ix>, D::Error>
+    where
+        N: Deserialize<'de>,
+        E: Deserialize<'de>,
+        Ix: IndexType,
+        D: Deserializer<'de>,
+    {
+        let dag = deserializer.deserialize_map(AdjacencyVisitor::new(true))?;
+        Ok(StableDag::from(dag).filter_map_owned(|_, n| n, |_, e| Some(e)))
+    }
+}
@@ -1,3 +1,14 @@
+//! Serde support for **Dag**s, enabled by the `serde-1` feature.
+//!
+//! **Dag** (and **StableDag**) implement `Serialize` and `Deserialize` by (de)serializing the
+//! underlying petgraph graph, i.e. a list of nodes and a list of edges by index. The
+//! [`adjacency`] module provides an alternative representation keyed by node IDs that is better
+//! suited to hand-written files, while the [`topological`] module provides a representation that
+//! includes a topological order of the nodes.
+
+pub mod adjacency;
+pub mod topological;
+
 use crate::Dag;
 use petgraph::algo::DfsSpace;
 use petgraph::graph::IndexType;
@@ -0,0 +1,217 @@
+//! A serde representation of a **Dag** that includes a topological order of its nodes.
+//!
+//! The graph is serialized as with the default `Dag` representation, alongside an `order` field
+//! listing every node index in topological order. On deserialization the order is verified in
+//! **O(|V| + |E|)** time by checking that it is a permutation of the nodes and that every edge
+//! points forward within it, which also proves that the graph is acyclic without a search.
+//!
+//! The [`serialize`] and [`deserialize`] functions may be used via
+//! `#[serde(with = "daggy::serde::topological")]` on a `Dag` field, in which case an order is
+//! computed when serializing and discarded after verification when deserializing. To choose the
+//! order, or to read the nodes in dependency order after deserializing, use a [`TopologicalDag`].
+
+use crate::{Dag, EdgeIndex, NodeIndex};
+use petgraph::algo::DfsSpace;
+use petgraph::graph::{DefaultIx, DiGraph, IndexType};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A **Dag** paired with a topological order of its nodes that is preserved when serialized.
+#[derive(Clone, Debug)]
+pub struct TopologicalDag<N, E, Ix: IndexType = DefaultIx> {
+    dag: Dag<N, E, Ix>,
+    order: Vec<NodeIndex<Ix>>,
+}
+
+/// The reason that a sequence of nodes is not a topological order of a **Dag**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OrderError<Ix = DefaultIx> {
+    /// The order does not contain every node exactly once.
+    NotAPermutation,
+    /// The edge at the given index points backward within the order.
+    BackwardEdge(EdgeIndex<Ix>),
+}
+
+#[derive(Serialize)]
+struct Repr<'a, N, E, Ix: IndexType> {
+    graph: &'a DiGraph<N, E, Ix>,
+    order: Vec<Ix>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "N: Deserialize<'de>, E: Deserialize<'de>, \
+                             Ix: IndexType + Deserialize<'de>"))]
+struct OwnedRepr<N, E, Ix: IndexType> {
+    graph: DiGraph<N, E, Ix>,
+    order: Vec<Ix>,
+}
+
+impl<N, E, Ix> TopologicalDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Pair the `Dag` with a topological order computed from its graph.
+    pub fn new(dag: Dag<N, E, Ix>) -> Self {
+        let order = dag.topological_order();
+        TopologicalDag { dag, order }
+    }
+
+    /// Pair the `Dag` with the given topological order.
+    ///
+    /// Returns an error if `order` does not contain every node exactly once or if any edge
+    /// points backward within it.
+    ///
+    /// Computes in **O(|V| + |E|)** time.
+    pub fn with_order(
+        dag: Dag<N, E, Ix>,
+        order: Vec<NodeIndex<Ix>>,
+    ) -> Result<Self, OrderError<Ix>> {
+        verify(&dag.graph, &order)?;
+        Ok(TopologicalDag { dag, order })
+    }
+
+    /// Borrow the inner `Dag`.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        &self.dag
+    }
+
+    /// The nodes of the `Dag` in topological order.
+    pub fn order(&self) -> &[NodeIndex<Ix>] {
+        &self.order
+    }
+
+    /// Take ownership of the `TopologicalDag` and return the inner `Dag` and order.
+    pub fn into_parts(self) -> (Dag<N, E, Ix>, Vec<NodeIndex<Ix>>) {
+        (self.dag, self.order)
+    }
+}
+
+impl<N, E, Ix> From<Dag<N, E, Ix>> for TopologicalDag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    fn from(dag: Dag<N, E, Ix>) -> Self {
+        TopologicalDag::new(dag)
+    }
+}
+
+impl<N, E, Ix> Serialize for TopologicalDag<N, E, Ix>
+where
+    N: Serialize,
+    E: Serialize,
+    Ix: IndexType + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let order = self.order.iter().map(|n| Ix::new(n.index())).collect();
+        let repr = Repr {
+            graph: &self.dag.graph,
+            order,
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, N, E, Ix> Deserialize<'de> for TopologicalDag<N, E, Ix>
+where
+    N: Deserialize<'de>,
+    E: Deserialize<'de>,
+    Ix: IndexType + Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let OwnedRepr { graph, order } = OwnedRepr::deserialize(deserializer)?;
+        let order: Vec<NodeIndex<Ix>> = order.into_iter().map(NodeIndex::from).collect();
+        if let Err(err) = verify(&graph, &order) {
+            let msg = match err {
+                OrderError::NotAPermutation => "order is not a permutation of the nodes".into(),
+                OrderError::BackwardEdge(e) => {
+                    let (a, b) = graph.edge_endpoints(e).expect("no edge");
+                    let (a, b) = (a.index(), b.index());
+                    format!("edge {} -> {} points backward within the order", a, b)
+                }
+            };
+            return Err(D::Error::custom(msg));
+        }
+        let cycle_state = DfsSpace::new(&graph);
+        let dag = Dag { graph, cycle_state };
+        Ok(TopologicalDag { dag, order })
+    }
+}
+
+/// Serialize the `Dag` along with a topological order of its nodes.
+pub fn serialize<N, E, Ix, S>(dag: &Dag<N, E, Ix>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    N: Serialize,
+    E: Serialize,
+    Ix: IndexType + Serialize,
+    S: Serializer,
+{
+    let order = dag
+        .topological_order()
+        .into_iter()
+        .map(|n| Ix::new(n.index()))
+        .collect();
+    let repr = Repr {
+        graph: &dag.graph,
+        order,
+    };
+    repr.serialize(serializer)
+}
+
+/// Deserialize a `Dag` along with a topological order of its nodes, verifying the order and
+/// discarding it.
+pub fn deserialize<'de, N, E, Ix, D>(deserializer: D) -> Result<Dag<N, E, Ix>, D::Error>
+where
+    N: Deserialize<'de>,
+    E: Deserialize<'de>,
+    Ix: IndexType + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    TopologicalDag::deserialize(deserializer).map(|t| t.dag)
+}
+
+// Check that `order` contains every node once and that every edge points forward within it.
+fn verify<N, E, Ix>(
+    graph: &DiGraph<N, E, Ix>,
+    order: &[NodeIndex<Ix>],
+) -> Result<(), OrderError<Ix>>
+where
+    Ix: IndexType,
+{
+    let n = graph.node_count();
+    if order.len() != n {
+        return Err(OrderError::NotAPermutation);
+    }
+    let mut position = vec![usize::MAX; n];
+    for (p, node) in order.iter().enumerate() {
+        match position.get_mut(node.index()) {
+            Some(slot) if *slot == usize::MAX => *slot = p,
+            _ => return Err(OrderError::NotAPermutation),
+        }
+    }
+    for (i, edge) in graph.raw_edges().iter().enumerate() {
+        if position[edge.source().index()] >= position[edge.target().index()] {
+            return Err(OrderError::BackwardEdge(EdgeIndex::new(i)));
+        }
+    }
+    Ok(())
+}
+
+impl<Ix> std::fmt::Display for OrderError<Ix>
+where
+    Ix: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OrderError::NotAPermutation => write!(f, "order is not a permutation of the nodes"),
+            OrderError::BackwardEdge(e) => write!(f, "edge {:?} points backward", e),
+        }
+    }
+}
+
+impl<Ix> std::error::Error for OrderError<Ix> where Ix: std::fmt::Debug {}
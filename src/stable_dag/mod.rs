@@ -3,9 +3,10 @@
 //! indices when a node is removed.
 
 use crate::walker;
-use crate::{Dag, WouldCycle};
+use crate::{find_path, Dag, WouldCycle};
 use petgraph as pg;
 use petgraph::algo::{has_path_connecting, DfsSpace};
+use petgraph::graph::Frozen;
 use petgraph::stable_graph::{DefaultIx, GraphIndex, IndexType, StableDiGraph};
 use petgraph::visit::{
     GetAdjacencyMatrix, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoEdgesDirected,
@@ -203,6 +204,21 @@ where
         StableDag { graph, cycle_state }
     }
 
+    // Like `filter_map`, but taking the weights by value.
+    pub(crate) fn filter_map_owned<F, G, N2, E2>(
+        self,
+        node_map: F,
+        edge_map: G,
+    ) -> StableDag<N2, E2, Ix>
+    where
+        F: FnMut(NodeIndex<Ix>, N) -> Option<N2>,
+        G: FnMut(EdgeIndex<Ix>, E) -> Option<E2>,
+    {
+        let graph = self.graph.filter_map_owned(node_map, edge_map);
+        let cycle_state = DfsSpace::new(&graph);
+        StableDag { graph, cycle_state }
+    }
+
     /// Removes all nodes and edges from the **StableDag**.
     pub fn clear(&mut self) {
         self.graph.clear();
@@ -422,6 +438,74 @@ where
         self.graph.clear_edges()
     }
 
+    /// Move the target of the edge at the given index to `new_target`, keeping the edge's weight
+    /// and index.
+    ///
+    /// Checks if the moved edge would create a cycle in the graph before making any changes. If
+    /// it would, the graph is left untouched and a `WouldCycle` error is returned containing the
+    /// existing path of nodes from `new_target` to the edge's source that the moved edge would
+    /// have closed into a cycle.
+    ///
+    /// **Panics** if there is no edge at the given index or if `new_target` does not exist.
+    pub fn retarget_edge(
+        &mut self,
+        e: EdgeIndex<Ix>,
+        new_target: NodeIndex<Ix>,
+    ) -> Result<(), WouldCycle<Vec<NodeIndex<Ix>>>> {
+        let (source, target) = self.edge_endpoints(e).expect("no edge for the given index");
+        assert!(self.contains_node(new_target), "no node for `new_target`");
+        if target == new_target {
+            return Ok(());
+        }
+        if must_check_for_cycle(self, source, new_target) {
+            if let Some(path) = find_path(&self.graph, new_target, source) {
+                return Err(WouldCycle(path));
+            }
+        }
+        let weight = self
+            .graph
+            .remove_edge(e)
+            .expect("no edge for the given index");
+        // The most recently vacated edge index is always the first to be reused.
+        let moved = self.graph.add_edge(source, new_target, weight);
+        debug_assert_eq!(moved, e);
+        Ok(())
+    }
+
+    /// Move the source of the edge at the given index to `new_source`, keeping the edge's weight
+    /// and index.
+    ///
+    /// Checks if the moved edge would create a cycle in the graph before making any changes. If
+    /// it would, the graph is left untouched and a `WouldCycle` error is returned containing the
+    /// existing path of nodes from the edge's target to `new_source` that the moved edge would
+    /// have closed into a cycle.
+    ///
+    /// **Panics** if there is no edge at the given index or if `new_source` does not exist.
+    pub fn resource_edge(
+        &mut self,
+        e: EdgeIndex<Ix>,
+        new_source: NodeIndex<Ix>,
+    ) -> Result<(), WouldCycle<Vec<NodeIndex<Ix>>>> {
+        let (source, target) = self.edge_endpoints(e).expect("no edge for the given index");
+        assert!(self.contains_node(new_source), "no node for `new_source`");
+        if source == new_source {
+            return Ok(());
+        }
+        if must_check_for_cycle(self, new_source, target) {
+            if let Some(path) = find_path(&self.graph, target, new_source) {
+                return Err(WouldCycle(path));
+            }
+        }
+        let weight = self
+            .graph
+            .remove_edge(e)
+            .expect("no edge for the given index");
+        // The most recently vacated edge index is always the first to be reused.
+        let moved = self.graph.add_edge(new_source, target, weight);
+        debug_assert_eq!(moved, e);
+        Ok(())
+    }
+
     /// Add a new edge and parent node to the node at the given `NodeIndex`.
     ///
     /// Returns both the edge's `EdgeIndex` and the node's `NodeIndex`.
@@ -533,6 +617,52 @@ where
         self.graph.remove_edge(e)
     }
 
+    /// Keep all nodes for which the `visit` closure returns `true` and remove all others, along
+    /// with their edges.
+    ///
+    /// `visit` is provided a proxy reference to the `StableDag`, so that the `StableDag` may be
+    /// walked and its weights modified.
+    ///
+    /// The indices of the removed nodes and edges are invalidated, but no others.
+    ///
+    /// Removing nodes can never create a cycle, so no cycle checks are required.
+    ///
+    /// Computes in **O(n + e')** time, where **n** is the number of node indices and **e'** is the
+    /// number of edges connected to removed nodes.
+    pub fn retain_nodes<F>(&mut self, mut visit: F)
+    where
+        F: FnMut(Frozen<Self>, NodeIndex<Ix>) -> bool,
+    {
+        for i in 0..self.graph.node_bound() {
+            let node = NodeIndex::new(i);
+            if self.contains_node(node) && !visit(Frozen::new(self), node) {
+                self.graph.remove_node(node);
+            }
+        }
+    }
+
+    /// Keep all edges for which the `visit` closure returns `true` and remove all others.
+    ///
+    /// `visit` is provided a proxy reference to the `StableDag`, so that the `StableDag` may be
+    /// walked and its weights modified.
+    ///
+    /// The indices of the removed edges are invalidated, but no others.
+    ///
+    /// Removing edges can never create a cycle, so no cycle checks are required.
+    ///
+    /// Computes in **O(e')** time, where **e'** is the number of edge indices.
+    pub fn retain_edges<F>(&mut self, mut visit: F)
+    where
+        F: FnMut(Frozen<Self>, EdgeIndex<Ix>) -> bool,
+    {
+        for i in 0..pg::visit::EdgeIndexable::edge_bound(&self.graph) {
+            let edge = EdgeIndex::new(i);
+            if self.edge_weight(edge).is_some() && !visit(Frozen::new(self), edge) {
+                self.graph.remove_edge(edge);
+            }
+        }
+    }
+
     /// A **Walker** type that may be used to step through the parents of the given child node.
     ///
     /// Unlike iterator types, **Walker**s do not require borrowing the internal **Graph**. This
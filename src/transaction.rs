@@ -0,0 +1,277 @@
+//! Atomic, all-or-nothing mutation of a **Dag** via [`Dag::transaction`].
+
+use crate::edit::Edit;
+use crate::{must_check_for_cycle, Dag, EdgeIndex, NodeIndex};
+use petgraph as pg;
+use petgraph::graph::{DefaultIx, IndexType};
+
+/// A set of pending mutations to a **Dag**, created by [`Dag::transaction`].
+///
+/// Mutations are applied to the **Dag** immediately so that they may be observed via
+/// [`dag`](Transaction::dag), however edges are added **without** checking for cycles. Whether or
+/// not the **Dag** is acyclic is only checked once when the transaction commits.
+///
+/// Each mutation records the edit that reverses it. If the transaction is aborted, fails its cycle
+/// check or is dropped during a panic, these edits are applied in reverse order, restoring the
+/// **Dag** to its exact prior state including the positions of all node and edge indices and the
+/// order in which each node's neighbours are visited.
+pub struct Transaction<'a, N, E, Ix: IndexType = DefaultIx> {
+    dag: &'a mut Dag<N, E, Ix>,
+    undo: Vec<Edit<N, E, Ix>>,
+    // The age of each edge prior to the first removal, used to restore the adjacency order.
+    ages: Option<Vec<usize>>,
+    should_check_for_cycle: bool,
+}
+
+/// The error returned by a failed [`Dag::transaction`].
+///
+/// In either case the **Dag** is rolled back to its state prior to the transaction.
+pub enum TransactionError<Err> {
+    /// The transaction closure returned an error.
+    Aborted(Err),
+    /// The mutations made within the transaction would have created a cycle.
+    WouldCycle,
+}
+
+impl<N, E, Ix> Dag<N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Apply a group of mutations to the `Dag` atomically.
+    ///
+    /// The given closure may freely add and remove nodes and edges and replace weights via the
+    /// given [`Transaction`]. Cycle checks are deferred until the closure returns `Ok`, at which
+    /// point the whole graph is checked at most once.
+    ///
+    /// If the closure returns an `Err`, or if the mutations would have created a cycle, the `Dag`
+    /// is rolled back to exactly its prior state (including all indices and the order of each
+    /// node's neighbours) and a `TransactionError` is returned. Otherwise, the mutations are kept
+    /// and the closure's result is returned.
+    ///
+    /// Rolling back takes time proportional to the number of mutations made. If any node or edge
+    /// was removed, the first removal and the rollback each also take **O(|V| + |E| log |E|)**
+    /// time to record and restore the order of each node's neighbours.
+    pub fn transaction<F, T, Err>(&mut self, f: F) -> Result<T, TransactionError<Err>>
+    where
+        F: FnOnce(&mut Transaction<N, E, Ix>) -> Result<T, Err>,
+    {
+        let mut tx = Transaction {
+            dag: self,
+            undo: vec![],
+            ages: None,
+            should_check_for_cycle: false,
+        };
+        let value = f(&mut tx).map_err(TransactionError::Aborted)?;
+        if tx.should_check_for_cycle && pg::algo::is_cyclic_directed(&tx.dag.graph) {
+            return Err(TransactionError::WouldCycle);
+        }
+        tx.undo.clear();
+        tx.ages = None;
+        Ok(value)
+    }
+}
+
+impl<N, E, Ix> Transaction<'_, N, E, Ix>
+where
+    Ix: IndexType,
+{
+    /// Borrow the `Dag` in its current, partially mutated state.
+    ///
+    /// **Note:** As cycle checks are deferred until commit, the graph may currently contain
+    /// cycles.
+    pub fn dag(&self) -> &Dag<N, E, Ix> {
+        self.dag
+    }
+
+    /// Add a new node with the given weight and return its index.
+    pub fn add_node(&mut self, weight: N) -> NodeIndex<Ix> {
+        let node = self.dag.graph.add_node(weight);
+        self.undo.push(Edit::RemoveNode(node));
+        node
+    }
+
+    /// Add a new directed edge `a` -> `b` with the given weight and return its index.
+    ///
+    /// Unlike [`Dag::add_edge`], the edge is added without checking for cycles. The check is
+    /// performed when the transaction commits.
+    ///
+    /// **Panics** if either `a` or `b` do not exist.
+    pub fn add_edge(&mut self, a: NodeIndex<Ix>, b: NodeIndex<Ix>, weight: E) -> EdgeIndex<Ix> {
+        if !self.should_check_for_cycle {
+            self.should_check_for_cycle = must_check_for_cycle(self.dag, a, b);
+        }
+        let edge = self.dag.graph.add_edge(a, b, weight);
+        self.undo.push(Edit::RemoveEdge(edge));
+        edge
+    }
+
+    /// Add a new edge and child node to the node at the given index.
+    ///
+    /// As the new node only has a single edge, this never requires a cycle check on commit.
+    ///
+    /// **Panics** if the given parent node does not exist.
+    pub fn add_child(
+        &mut self,
+        parent: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let child = self.add_node(node);
+        let edge = self.add_edge(parent, child, edge);
+        (edge, child)
+    }
+
+    /// Add a new edge and parent node to the node at the given index.
+    ///
+    /// As the new node only has a single edge, this never requires a cycle check on commit.
+    ///
+    /// **Panics** if the given child node does not exist.
+    pub fn add_parent(
+        &mut self,
+        child: NodeIndex<Ix>,
+        edge: E,
+        node: N,
+    ) -> (EdgeIndex<Ix>, NodeIndex<Ix>) {
+        let parent = self.add_node(node);
+        let edge = self.add_edge(parent, child, edge);
+        (edge, parent)
+    }
+
+    /// Update the weight of the edge `a` -> `b` if it exists, otherwise add it.
+    ///
+    /// **Panics** if either `a` or `b` do not exist.
+    pub fn update_edge(&mut self, a: NodeIndex<Ix>, b: NodeIndex<Ix>, weight: E) -> EdgeIndex<Ix> {
+        match self.dag.find_edge(a, b) {
+            Some(edge) => {
+                self.set_edge_weight(edge, weight);
+                edge
+            }
+            None => self.add_edge(a, b, weight),
+        }
+    }
+
+    /// Remove the node at the given index along with all of its edges.
+    ///
+    /// As with [`Dag::remove_node`], the last node is moved into the index of the removed node.
+    ///
+    /// The removed weight is retained in case of rollback, so a clone is returned.
+    pub fn remove_node(&mut self, node: NodeIndex<Ix>) -> Option<N>
+    where
+        N: Clone,
+    {
+        let weight = self.dag.node_weight(node)?.clone();
+        self.record_ages();
+        let undo = self.dag.apply_edit(Edit::RemoveNode(node));
+        self.undo.push(undo);
+        Some(weight)
+    }
+
+    /// Remove the edge at the given index.
+    ///
+    /// As with [`Dag::remove_edge`], the last edge is moved into the index of the removed edge.
+    ///
+    /// The removed weight is retained in case of rollback, so a clone is returned.
+    pub fn remove_edge(&mut self, edge: EdgeIndex<Ix>) -> Option<E>
+    where
+        E: Clone,
+    {
+        let weight = self.dag.edge_weight(edge)?.clone();
+        self.record_ages();
+        let undo = self.dag.apply_edit(Edit::RemoveEdge(edge));
+        self.undo.push(undo);
+        Some(weight)
+    }
+
+    /// Replace the weight of the node at the given index.
+    ///
+    /// **Panics** if the node does not exist.
+    pub fn set_node_weight(&mut self, node: NodeIndex<Ix>, weight: N) {
+        let undo = self.dag.apply_edit(Edit::NodeWeight(node, weight));
+        self.undo.push(undo);
+    }
+
+    /// Replace the weight of the edge at the given index.
+    ///
+    /// **Panics** if the edge does not exist.
+    pub fn set_edge_weight(&mut self, edge: EdgeIndex<Ix>, weight: E) {
+        let undo = self.dag.apply_edit(Edit::EdgeWeight(edge, weight));
+        self.undo.push(undo);
+    }
+
+    /// Mutably borrow the weight of the node at the given index.
+    ///
+    /// A clone of the current weight is retained in case of rollback.
+    pub fn node_weight_mut(&mut self, node: NodeIndex<Ix>) -> Option<&mut N>
+    where
+        N: Clone,
+    {
+        let old = self.dag.node_weight(node)?.clone();
+        self.undo.push(Edit::NodeWeight(node, old));
+        self.dag.node_weight_mut(node)
+    }
+
+    /// Mutably borrow the weight of the edge at the given index.
+    ///
+    /// A clone of the current weight is retained in case of rollback.
+    pub fn edge_weight_mut(&mut self, edge: EdgeIndex<Ix>) -> Option<&mut E>
+    where
+        E: Clone,
+    {
+        let old = self.dag.edge_weight(edge)?.clone();
+        self.undo.push(Edit::EdgeWeight(edge, old));
+        self.dag.edge_weight_mut(edge)
+    }
+
+    // Record the age of every edge before the first removal, which is the only kind of mutation
+    // whose reversal changes the order of any adjacency list.
+    fn record_ages(&mut self) {
+        if self.ages.is_none() {
+            self.ages = Some(self.dag.edge_ages());
+        }
+    }
+}
+
+impl<N, E, Ix> Drop for Transaction<'_, N, E, Ix>
+where
+    Ix: IndexType,
+{
+    // Roll back any edits that were not committed.
+    fn drop(&mut self) {
+        while let Some(edit) = self.undo.pop() {
+            self.dag.apply_edit(edit);
+        }
+        // Edges added before the first removal have since been removed again, so the remaining
+        // edges are exactly those that existed when the ages were recorded.
+        if let Some(ages) = self.ages.take() {
+            self.dag.restore_adjacency(&ages);
+        }
+    }
+}
+
+impl<Err> std::fmt::Debug for TransactionError<Err>
+where
+    Err: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TransactionError::Aborted(err) => f.debug_tuple("Aborted").field(err).finish(),
+            TransactionError::WouldCycle => write!(f, "WouldCycle"),
+        }
+    }
+}
+
+impl<Err> std::fmt::Display for TransactionError<Err>
+where
+    Err: std::fmt::Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TransactionError::Aborted(err) => write!(f, "transaction aborted: {}", err),
+            TransactionError::WouldCycle => {
+                write!(f, "transaction would have created a cycle")
+            }
+        }
+    }
+}
+
+impl<Err> std::error::Error for TransactionError<Err> where Err: std::fmt::Debug + std::fmt::Display {}
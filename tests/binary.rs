@@ -0,0 +1,183 @@
+extern crate daggy;
+
+use daggy::binary::{BinaryError, MAGIC, VERSION};
+use daggy::Dag;
+use std::convert::TryInto;
+
+fn write(dag: &Dag<u32, u32>) -> Vec<u8> {
+    let mut bytes = vec![];
+    dag.write_binary(
+        &mut bytes,
+        |n, buf| buf.extend(n.to_le_bytes()),
+        |e, buf| buf.extend(e.to_le_bytes()),
+    )
+    .unwrap();
+    bytes
+}
+
+fn read(bytes: &[u8]) -> Result<Dag<u32, u32>, BinaryError> {
+    let decode = |b: &[u8]| {
+        let bytes = b
+            .try_into()
+            .map_err(|_| format!("expected 4 bytes, found {}", b.len()))?;
+        Ok(u32::from_le_bytes(bytes))
+    };
+    Dag::read_binary(bytes, decode, decode)
+}
+
+fn edges(dag: &Dag<u32, u32>) -> Vec<(usize, usize, u32)> {
+    let mut edges: Vec<_> = dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    edges.sort();
+    edges
+}
+
+#[test]
+fn round_trip() {
+    // Nodes are added out of topological order, with a parallel edge.
+    let mut dag = Dag::<u32, u32>::new();
+    let nodes: Vec<_> = (0..6).map(|i| dag.add_node(i * 10)).collect();
+    for &(a, b, w) in &[
+        (5, 0, 1),
+        (5, 3, 2),
+        (3, 0, 3),
+        (3, 0, 4),
+        (1, 5, 5),
+        (4, 2, 6),
+    ] {
+        dag.add_edge(nodes[a], nodes[b], w).unwrap();
+    }
+    let bytes = write(&dag);
+    assert_eq!(bytes[..4], MAGIC);
+    assert_eq!(bytes[4], VERSION);
+
+    let read = read(&bytes).unwrap();
+    let weights: Vec<_> = read.raw_nodes().iter().map(|n| n.weight).collect();
+    assert_eq!(weights, vec![0, 10, 20, 30, 40, 50]);
+    assert_eq!(edges(&read), edges(&dag));
+}
+
+#[test]
+fn rejects_invalid_input() {
+    let mut dag = Dag::<u32, u32>::new();
+    let a = dag.add_node(1);
+    dag.add_child(a, 2, 3);
+    let bytes = write(&dag);
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(read(&bad_magic), Err(BinaryError::InvalidMagic)));
+
+    let mut future = bytes.clone();
+    future[4] = VERSION + 1;
+    assert!(matches!(read(&future), Err(BinaryError::UnsupportedVersion(v)) if v == VERSION + 1));
+
+    for len in 0..bytes.len() {
+        assert!(read(&bytes[..len]).is_err(), "truncated at {}", len);
+    }
+
+    // Header, then a single node with index 0, a 0-byte weight and an edge to itself.
+    let mut self_loop = MAGIC.to_vec();
+    self_loop.extend([VERSION, 0, 1, 1, 1, 0, 0, 1, 0, 0]);
+    assert!(matches!(
+        Dag::<(), ()>::read_binary(&self_loop[..], |_| Ok(()), |_| Ok(())),
+        Err(BinaryError::WouldCycle(0))
+    ));
+
+    // A parent distance pointing before the first record.
+    let mut out_of_range = MAGIC.to_vec();
+    out_of_range.extend([VERSION, 0, 1, 1, 1, 0, 0, 1, 5, 0]);
+    assert!(matches!(
+        Dag::<(), ()>::read_binary(&out_of_range[..], |_| Ok(()), |_| Ok(())),
+        Err(BinaryError::Corrupt(_))
+    ));
+    // A single node whose index is just within a huge bound, which must not be used to size
+    // any allocation.
+    let mut huge = MAGIC.to_vec();
+    huge.extend([VERSION, 0, 1, 0]);
+    let mut varint = |mut n: u64| {
+        while n >= 0x80 {
+            huge.push(n as u8 | 0x80);
+            n >>= 7;
+        }
+        huge.push(n as u8);
+    };
+    varint(1 << 62);
+    varint((1 << 62) - 1);
+    assert_eq!(huge.len(), 26);
+    assert!(matches!(
+        Dag::<(), ()>::read_binary(&huge[..], |_| Ok(()), |_| Ok(())),
+        Err(BinaryError::Corrupt(_))
+    ));
+    assert!(matches!(
+        Dag::<(), (), usize>::read_binary(&huge[..], |_| Ok(()), |_| Ok(())),
+        Err(BinaryError::Io(_))
+    ));
+    huge.extend([0, 0]);
+    let read = Dag::<(), (), usize>::read_binary(&huge[..], |_| Ok(()), |_| Ok(())).unwrap();
+    assert_eq!(read.node_count(), 1);
+
+    // Two records with the same node index.
+    let mut repeated = MAGIC.to_vec();
+    repeated.extend([VERSION, 0, 2, 0, 2, 1, 0, 0, 1, 0, 0]);
+    assert!(matches!(
+        Dag::<(), ()>::read_binary(&repeated[..], |_| Ok(()), |_| Ok(())),
+        Err(BinaryError::Corrupt(_))
+    ));
+}
+
+#[cfg(feature = "stable_dag")]
+#[test]
+fn stable_dag_compacts_vacant_indices() {
+    use daggy::stable_dag::StableDag;
+
+    let mut dag = StableDag::<u32, u32>::new();
+    let a = dag.add_node(1);
+    let b = dag.add_node(2);
+    let c = dag.add_node(3);
+    dag.add_edge(c, a, 9).unwrap();
+    dag.remove_node(b);
+    let mut bytes = vec![];
+    dag.write_binary(
+        &mut bytes,
+        |n, buf| buf.extend(n.to_le_bytes()),
+        |e, buf| buf.extend(e.to_le_bytes()),
+    )
+    .unwrap();
+    let decode = |b: &[u8]| Ok(u32::from_le_bytes(b.try_into().unwrap()));
+    let read = StableDag::<u32, u32>::read_binary(&bytes[..], decode, decode).unwrap();
+    let weights: Vec<_> = read.graph().node_weights().copied().collect();
+    assert_eq!(weights, vec![1, 3]);
+    assert_eq!(read.edge_count(), 1);
+    assert!(read.find_edge(1.into(), 0.into()).is_some());
+}
+
+#[cfg(feature = "serde-1")]
+#[test]
+fn serde_round_trip() {
+    let mut dag = Dag::<String, Option<u32>>::new();
+    let root = dag.add_node("root".to_string());
+    let (_, a) = dag.add_child(root, Some(1), "a".to_string());
+    let (_, b) = dag.add_child(root, None, "b".to_string());
+    dag.add_edge(a, b, Some(3)).unwrap();
+
+    let mut bytes = vec![];
+    dag.write_binary_serde(&mut bytes).unwrap();
+    let read = Dag::<String, Option<u32>>::read_binary_serde(&bytes[..]).unwrap();
+    let weights: Vec<_> = read.raw_nodes().iter().map(|n| &n.weight[..]).collect();
+    assert_eq!(weights, ["root", "a", "b"]);
+    let mut edges: Vec<_> = read
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    edges.sort();
+    assert_eq!(edges, vec![(0, 1, Some(1)), (0, 2, None), (1, 2, Some(3))]);
+
+    // Weights of the wrong type are rejected rather than misread.
+    let err = Dag::<u64, Option<u32>>::read_binary_serde(&bytes[..]).unwrap_err();
+    assert!(matches!(err, BinaryError::InvalidWeight(_)), "{:?}", err);
+}
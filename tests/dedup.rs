@@ -0,0 +1,66 @@
+extern crate daggy;
+
+use daggy::{Dag, NodeIndex};
+
+#[test]
+fn dedup_expression() {
+    // (x + y) * (x + y), with each operand built separately.
+    let mut dag = Dag::<&str, u8>::new();
+    let mul = dag.add_node("*");
+    let mut sums = vec![];
+    for side in 0..2 {
+        let (_, add) = dag.add_child(mul, side, "+");
+        dag.add_child(add, 0, "x");
+        dag.add_child(add, 1, "y");
+        sums.push(add);
+    }
+    // A third sum whose operands are swapped is not merged.
+    let (_, add) = dag.add_child(mul, 2, "+");
+    dag.add_child(add, 1, "x");
+    dag.add_child(add, 0, "y");
+
+    let (remap, eliminated) = dag.dedup_subgraphs(|a, b| a == b);
+    assert_eq!(eliminated, 5);
+    assert_eq!(dag.node_count(), 5);
+    assert_eq!(remap[sums[0].index()], remap[sums[1].index()]);
+    assert_ne!(remap[sums[0].index()], remap[add.index()]);
+    let mul = remap[mul.index()];
+    assert_eq!(mul, NodeIndex::new(0));
+    // The multiplication still has one edge per operand.
+    assert_eq!(dag.graph().edges(mul).count(), 3);
+    assert!(dag.find_edge(mul, remap[sums[1].index()]).is_some());
+}
+
+#[test]
+fn dedup_keeps_lowest_index() {
+    // Parents are visited before the lower-indexed duplicate in reverse topological order.
+    let mut dag = Dag::<&str, ()>::new();
+    let a = dag.add_node("leaf");
+    let p = dag.add_node("p");
+    let b = dag.add_node("leaf");
+    let q = dag.add_node("p");
+    dag.add_edge(q, a, ()).unwrap();
+    dag.add_edge(p, b, ()).unwrap();
+    let (remap, eliminated) = dag.dedup_subgraphs(|a, b| a == b);
+    assert_eq!(eliminated, 2);
+    assert_eq!(remap, vec![a, p, a, p]);
+    assert_eq!(dag.edge_count(), 1);
+    assert!(dag.find_edge(p, a).is_some());
+}
+
+#[test]
+fn dedup_by_key_calls_node_key_once_per_node() {
+    let mut dag = Dag::<u32, ()>::new();
+    let root = dag.add_node(0);
+    for i in 1..1000 {
+        dag.add_child(root, (), i % 500);
+    }
+    let mut calls = 0;
+    let (_, eliminated) = dag.dedup_subgraphs_by_key(|&n| {
+        calls += 1;
+        n
+    });
+    assert_eq!(calls, 1000);
+    assert_eq!(eliminated, 499);
+    assert_eq!(dag.node_count(), 501);
+}
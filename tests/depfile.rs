@@ -0,0 +1,97 @@
+extern crate daggy;
+
+use daggy::depfile::{self, DepfileError, Depfiles};
+use daggy::Walker;
+use std::path::{Path, PathBuf};
+
+fn targets_of(depfiles: &Depfiles, path: &str) -> Vec<PathBuf> {
+    let dag = depfiles.dag();
+    let node = depfiles.node(Path::new(path)).unwrap();
+    let mut targets: Vec<_> = dag
+        .children(node)
+        .iter(dag)
+        .map(|(_, n)| dag[n].clone())
+        .collect();
+    targets.sort();
+    targets
+}
+
+#[test]
+fn unescapes_paths() {
+    let d = "# generated\r\n\
+             out/main.o out/main.d: C:\\src\\main.c \\\r\n\
+             \x20 a\\ b.h c\\\\\\ d.h e\\\\ f.h \\#g.h $$h.h \\\n\
+             \x20 | order-only # trailing comment\n\
+             \n";
+    let dag = depfile::parse("main.d", d).unwrap();
+    let paths: Vec<_> = dag.raw_nodes().iter().map(|n| n.weight.clone()).collect();
+    let expected = [
+        "out/main.o",
+        "out/main.d",
+        "C:\\src\\main.c",
+        "a b.h",
+        "c\\ d.h",
+        "e\\\\",
+        "f.h",
+        "#g.h",
+        "$h.h",
+        "order-only",
+    ];
+    assert_eq!(
+        paths,
+        expected.iter().map(PathBuf::from).collect::<Vec<_>>()
+    );
+    assert_eq!(dag.edge_count(), 16);
+
+    match depfile::parse("bad.d", "a: b\nc d\n") {
+        Err(DepfileError::Syntax { file, line, .. }) => {
+            assert_eq!((file, line), (PathBuf::from("bad.d"), 2))
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn merges_duplicate_targets() {
+    let mut depfiles = Depfiles::new();
+    depfiles
+        .parse("a.d", "a.o: a.c common.h\na.o: common.h\ncommon.h:\n")
+        .unwrap();
+    depfiles.parse("b.d", "b.o: b.c common.h\n").unwrap();
+    assert_eq!(depfiles.dag().node_count(), 5);
+    assert_eq!(depfiles.dag().edge_count(), 4);
+    assert_eq!(
+        targets_of(&depfiles, "common.h"),
+        [PathBuf::from("a.o"), PathBuf::from("b.o")]
+    );
+}
+
+#[test]
+fn reports_cycles_and_leaves_dag_unchanged() {
+    let mut depfiles = Depfiles::new();
+    depfiles.parse("gen.d", "gen.h: gen.py\n").unwrap();
+    let err = depfiles
+        .parse("loop.d", "tool: new.c \\\n  gen.h\ngen.py: tool\n")
+        .unwrap_err();
+    match err {
+        DepfileError::WouldCycle {
+            ref file,
+            line,
+            ref prerequisite,
+            ref target,
+        } => {
+            assert_eq!(file, Path::new("loop.d"));
+            assert_eq!(line, 3);
+            assert_eq!(prerequisite, Path::new("tool"));
+            assert_eq!(target, Path::new("gen.py"));
+        }
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        "loop.d:3: dependency of gen.py on tool would create a cycle"
+    );
+    assert_eq!(depfiles.dag().node_count(), 2);
+    assert_eq!(depfiles.dag().edge_count(), 1);
+    assert!(depfiles.node(Path::new("tool")).is_none());
+}
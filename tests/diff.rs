@@ -0,0 +1,208 @@
+extern crate daggy;
+
+use daggy::diff::{diff, DagDiff, EdgeChange, EdgeDiff, NodeChange, PatchError};
+use daggy::Dag;
+
+type Node = (&'static str, u32);
+
+fn key(node: &Node) -> &'static str {
+    node.0
+}
+
+fn pipeline() -> Dag<Node, &'static str> {
+    let mut dag = Dag::new();
+    let fetch = dag.add_node(("fetch", 0));
+    let (_, parse) = dag.add_child(fetch, "raw", ("parse", 0));
+    let (_, lint) = dag.add_child(parse, "ast", ("lint", 0));
+    dag.add_child(parse, "ast", ("build", 0));
+    dag.add_child(lint, "report", ("publish", 0));
+    dag
+}
+
+#[test]
+fn diff_by_key() {
+    let old = pipeline();
+    let mut new = Dag::<Node, &str>::new();
+    // The same pipeline built in a different order, with some changes.
+    let build = new.add_node(("build", 1));
+    let parse = new.add_node(("parse", 0));
+    let fetch = new.add_node(("fetch", 0));
+    let test = new.add_node(("test", 0));
+    new.add_edge(fetch, parse, "bytes").unwrap();
+    new.add_edge(parse, build, "ast").unwrap();
+    new.add_edge(build, test, "bin").unwrap();
+
+    let d = diff(&old, &new, key, |a, b| a == b);
+    assert_eq!(d.added_nodes, vec![("test", ("test", 0))]);
+    assert_eq!(
+        d.removed_nodes,
+        vec![("lint", ("lint", 0)), ("publish", ("publish", 0))]
+    );
+    assert_eq!(
+        d.modified_nodes,
+        vec![NodeChange {
+            key: "build",
+            old: ("build", 0),
+            new: ("build", 1),
+        }]
+    );
+    assert_eq!(
+        d.modified_edges,
+        vec![EdgeChange {
+            source: "fetch",
+            target: "parse",
+            occurrence: 0,
+            old: "raw",
+            new: "bytes",
+        }]
+    );
+    assert_eq!(d.removed_edges.len(), 2);
+    assert_eq!(
+        d.added_edges,
+        vec![EdgeDiff {
+            source: "build",
+            target: "test",
+            occurrence: 0,
+            weight: "bin",
+        }]
+    );
+    assert!(diff(&new, &new, key, |a, b| a == b).is_empty());
+
+    // Applying the patch to the old dag produces a dag with no differences to the new one.
+    let mut patched = old.clone();
+    patched.apply(&d, key, |a, b| a == b).unwrap();
+    assert!(diff(&patched, &new, key, |a, b| a == b).is_empty());
+}
+
+#[test]
+fn apply_conflicts_leave_dag_untouched() {
+    let old = pipeline();
+    let mut new = old.clone();
+    let build = new.add_node(("build2", 0));
+    new.add_edge(daggy::NodeIndex::new(4), build, "x").unwrap();
+    new[daggy::NodeIndex::new(3)].1 = 2;
+    let d = diff(&old, &new, key, |a, b| a == b);
+
+    let mut target = old.clone();
+    target[daggy::NodeIndex::new(3)].1 = 9;
+    assert_eq!(
+        target.apply(&d, key, |a, b| a == b),
+        Err(PatchError::NodeModified("build"))
+    );
+    assert_eq!(target.node_count(), old.node_count());
+    assert_eq!(target.edge_count(), old.edge_count());
+
+    // Already applied.
+    let mut target = new.clone();
+    assert!(target.apply(&d, key, |a, b| a == b).is_err());
+}
+
+#[test]
+fn apply_would_cycle() {
+    let old = pipeline();
+    let mut new = old.clone();
+    let publish = daggy::NodeIndex::new(4);
+    let lint = daggy::NodeIndex::new(2);
+    new.add_edge(publish, daggy::NodeIndex::new(3), "loop")
+        .unwrap();
+    let d = diff(&old, &new, key, |a, b| a == b);
+
+    // Another change made in the meantime closes a cycle with the patch.
+    let mut target = old.clone();
+    let build = daggy::NodeIndex::new(3);
+    target.add_edge(build, lint, "back").unwrap();
+    assert_eq!(
+        target.apply(&d, key, |a, b| a == b),
+        Err(PatchError::WouldCycle)
+    );
+    assert_eq!(target.edge_count(), old.edge_count() + 1);
+}
+
+#[test]
+fn apply_parallel_edges() {
+    let mut old = Dag::<Node, &str>::new();
+    let a = old.add_node(("a", 0));
+    let b = old.add_node(("b", 0));
+    old.add_edge(a, b, "x").unwrap();
+    old.add_edge(a, b, "y").unwrap();
+
+    // Only the first of the two parallel edges is modified.
+    let mut new = old.clone();
+    new[daggy::EdgeIndex::new(0)] = "w";
+    let d = diff(&old, &new, key, |a, b| a == b);
+    assert_eq!(d.modified_edges[0].occurrence, 0);
+    let mut patched = old.clone();
+    patched.apply(&d, key, |a, b| a == b).unwrap();
+    let weights: Vec<_> = patched.raw_edges().iter().map(|e| e.weight).collect();
+    assert_eq!(weights, vec!["w", "y"]);
+    assert!(diff(&patched, &new, key, |a, b| a == b).is_empty());
+
+    // Removing the first edge is reported as the removal of that edge.
+    let mut new = old.clone();
+    new.remove_edge(daggy::EdgeIndex::new(0));
+    let d = diff(&old, &new, key, |a, b| a == b);
+    assert!(d.modified_edges.is_empty());
+    assert_eq!(d.removed_edges[0].occurrence, 0);
+    assert_eq!(d.removed_edges[0].weight, "x");
+    let mut patched = old.clone();
+    patched.apply(&d, key, |a, b| a == b).unwrap();
+    assert!(diff(&patched, &new, key, |a, b| a == b).is_empty());
+}
+
+#[test]
+fn apply_stale_edge_weight() {
+    let old = pipeline();
+    let mut new = old.clone();
+    let raw = daggy::EdgeIndex::new(0);
+    let report = daggy::EdgeIndex::new(3);
+    new[raw] = "bytes";
+    new.remove_edge(report);
+    let d = diff(&old, &new, key, |a, b| a == b);
+
+    let mut target = old.clone();
+    target[raw] = "text";
+    assert_eq!(
+        target.apply(&d, key, |a, b| a == b),
+        Err(PatchError::EdgeModified("fetch", "parse"))
+    );
+    assert_eq!(target[raw], "text");
+
+    let mut target = old.clone();
+    target[report] = "summary";
+    assert_eq!(
+        target.apply(&d, key, |a, b| a == b),
+        Err(PatchError::EdgeModified("lint", "publish"))
+    );
+    assert_eq!(target.edge_count(), old.edge_count());
+}
+
+#[test]
+fn apply_hand_built_diff() {
+    let mut dag = Dag::<Node, &str>::new();
+    dag.add_node(("a", 0));
+    let b = dag.add_node(("b", 0));
+    dag.add_node(("c", 0));
+
+    // Renaming `c` changes its key, after which removing `a` moves it into index 0.
+    let d = DagDiff {
+        added_nodes: vec![],
+        removed_nodes: vec![("a", ("a", 0))],
+        modified_nodes: vec![NodeChange {
+            key: "c",
+            old: ("c", 0),
+            new: ("C", 0),
+        }],
+        added_edges: vec![EdgeDiff {
+            source: "b",
+            target: "c",
+            occurrence: 0,
+            weight: "x",
+        }],
+        removed_edges: vec![],
+        modified_edges: vec![],
+    };
+    dag.apply(&d, key, |a, b| a == b).unwrap();
+    let c = daggy::NodeIndex::new(0);
+    assert_eq!(dag[c], ("C", 0));
+    assert!(dag.find_edge(b, c).is_some());
+}
@@ -0,0 +1,73 @@
+extern crate daggy;
+
+use daggy::dot::{FromDotError, RankDir};
+use daggy::Dag;
+
+#[test]
+fn round_trip() {
+    let mut dag = Dag::<&str, &str>::new();
+    let root = dag.add_node("root");
+    let (_, a) = dag.add_child(root, "x", "a \"quoted\"");
+    let (_, b) = dag.add_child(root, "y", "b\\c");
+    dag.add_child(a, "z", "multi\nline");
+    dag.add_edge(b, a, "").unwrap();
+
+    let dot = dag.to_dot(
+        RankDir::TopToBottom,
+        |_, n| vec![("label".into(), n.to_string())],
+        |_, e| vec![("label".into(), e.to_string())],
+    );
+    assert!(dot.contains("rankdir=TB;"));
+    let parsed = Dag::from_dot(&dot).unwrap();
+    let expected = dag.map(|_, n| n.to_string(), |_, e| e.to_string());
+    assert!(parsed.is_isomorphic_to(&expected, |a, b| a == b, |a, b| a == b));
+    for (p, e) in parsed.raw_nodes().iter().zip(expected.raw_nodes()) {
+        assert_eq!(p.weight, e.weight);
+    }
+}
+
+#[test]
+fn parse_features() {
+    let dot = r#"
+        /* A block comment. */
+        strict digraph "G" {
+            graph [rankdir=LR]; node [shape=box]
+            # a preprocessor line
+            a:port:n -> { b c } -> d [label="e" + "dge"];
+            a -> b // a duplicate, merged by `strict`
+            subgraph cluster_0 { e; f [label=<<b>F</b>>] }
+            d [label=D]
+            size = "4,4"
+        }
+    "#;
+    let dag = Dag::from_dot(dot).unwrap();
+    let weights: Vec<_> = dag.raw_nodes().iter().map(|n| n.weight.as_str()).collect();
+    assert_eq!(weights, ["a", "b", "c", "D", "e", "<b>F</b>"]);
+    assert_eq!(dag.edge_count(), 4);
+    assert!(dag.raw_edges().iter().all(|e| e.weight == "edge"));
+}
+
+#[test]
+fn parse_errors() {
+    let cyclic = Dag::from_dot("digraph { a -> b; b -> c;\n c -> a }");
+    assert_eq!(
+        cyclic.unwrap_err(),
+        FromDotError::WouldCycle {
+            source: "c".into(),
+            target: "a".into(),
+        }
+    );
+    let undirected = Dag::from_dot("graph { a -- b }");
+    assert_eq!(undirected.unwrap_err(), FromDotError::Undirected);
+    match Dag::from_dot("digraph {\n a -> ;\n}") {
+        Err(FromDotError::Syntax { line: 2, .. }) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let nested = format!("digraph {{ {}a{} }}", "{".repeat(256), "}".repeat(256));
+    assert_eq!(Dag::from_dot(&nested).unwrap().node_count(), 1);
+    let nested = format!("digraph {{ {} }}", "{".repeat(100_000));
+    match Dag::from_dot(&nested) {
+        Err(FromDotError::Syntax { line: 1, .. }) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
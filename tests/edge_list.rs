@@ -0,0 +1,98 @@
+extern crate daggy;
+
+use daggy::edge_list::{Duplicates, EdgeListError, EdgeListOptions};
+use daggy::Dag;
+
+fn weights(dag: &Dag<String, String>) -> (Vec<String>, Vec<(usize, usize, String)>) {
+    let nodes = dag.raw_nodes().iter().map(|n| n.weight.clone()).collect();
+    let edges = dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight.clone()))
+        .collect();
+    (nodes, edges)
+}
+
+#[test]
+fn round_trip() {
+    let mut dag = Dag::<String, String>::new();
+    let root = dag.add_node("# root".to_string());
+    let (_, a) = dag.add_child(root, "a, \"quoted\"".to_string(), " a ".to_string());
+    let (_, b) = dag.add_child(root, String::new(), "b\tc".to_string());
+    dag.add_edge(a, b, "x".to_string()).unwrap();
+
+    for options in [
+        EdgeListOptions::csv(),
+        EdgeListOptions::tsv().with_header(true),
+    ] {
+        let mut out = vec![];
+        dag.write_edge_list(&mut out, &options).unwrap();
+        let parsed = Dag::read_edge_list(&out[..], &options).unwrap();
+        assert_eq!(weights(&parsed), weights(&dag));
+    }
+
+    let mut out = vec![];
+    dag.add_child(b, "bad".to_string(), "line\nbreak".to_string());
+    let err = dag
+        .write_edge_list(&mut out, &EdgeListOptions::csv())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    // An empty name would be rejected when read back, so is rejected when written.
+    let mut dag = Dag::<String, String>::new();
+    let root = dag.add_node("root".to_string());
+    dag.add_child(root, String::new(), String::new());
+    let err = dag
+        .write_edge_list(&mut vec![], &EdgeListOptions::csv())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn duplicate_policies() {
+    let csv = "# deps\n\na,b,1\nb,c\n  # indented comment\na,b,2\n";
+    let read = |duplicates| {
+        let options = EdgeListOptions::csv().with_duplicates(duplicates);
+        Dag::read_edge_list(csv.as_bytes(), &options)
+    };
+    let labels = |dag: Dag<String, String>| -> Vec<String> {
+        dag.raw_edges().iter().map(|e| e.weight.clone()).collect()
+    };
+    assert_eq!(labels(read(Duplicates::Keep).unwrap()), ["1", "", "2"]);
+    assert_eq!(labels(read(Duplicates::Skip).unwrap()), ["1", ""]);
+    assert_eq!(labels(read(Duplicates::Replace).unwrap()), ["2", ""]);
+    match read(Duplicates::Error) {
+        Err(EdgeListError::DuplicateEdge {
+            line,
+            source,
+            target,
+        }) => assert_eq!((line, &source[..], &target[..]), (6, "a", "b")),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn reports_line_numbers() {
+    let options = EdgeListOptions::csv().with_header(true);
+    let read = |csv: &str| Dag::read_edge_list(csv.as_bytes(), &options).unwrap_err();
+
+    match read("from,to\na,b\nb,c\nc,d\nd,b\nc,a\n") {
+        EdgeListError::WouldCycle {
+            line,
+            source,
+            target,
+        } => assert_eq!((line, &source[..], &target[..]), (5, "d", "b")),
+        other => panic!("unexpected error: {:?}", other),
+    }
+    match read("from,to\na,b\n\"a,c\n") {
+        EdgeListError::Syntax { line, .. } => assert_eq!(line, 3),
+        other => panic!("unexpected error: {:?}", other),
+    }
+    match read("from,to\na,b,c,d\n") {
+        EdgeListError::Syntax { line, message } => {
+            assert_eq!(line, 2);
+            assert_eq!(message, "expected 2 or 3 fields, found 4");
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+}
@@ -0,0 +1,44 @@
+extern crate daggy;
+
+use daggy::Dag;
+
+// A diamond-heavy graph on which naive recursion would visit nodes exponentially many times.
+fn ladder(len: usize) -> Dag<(), u64> {
+    let mut dag = Dag::new();
+    let mut prev = dag.add_node(());
+    for _ in 0..len {
+        let (_, a) = dag.add_child(prev, 1, ());
+        let (_, b) = dag.add_child(prev, 2, ());
+        let next = dag.add_node(());
+        dag.add_edge(a, next, 0).unwrap();
+        dag.add_edge(b, next, 0).unwrap();
+        prev = next;
+    }
+    dag
+}
+
+#[test]
+fn fold_down_counts_paths() {
+    let dag = ladder(40);
+    let mut visits = 0;
+    let paths = dag.fold_down(
+        |_, _| {
+            visits += 1;
+            0u64
+        },
+        |count, _, &parent| count + parent.max(1),
+    );
+    assert_eq!(visits, dag.node_count());
+    assert_eq!(*paths.last().unwrap(), 1 << 40);
+}
+
+#[test]
+fn fold_up_with_edge_weights() {
+    let dag = ladder(3);
+    // The longest weighted path from each node to a sink.
+    let longest = dag.fold_up(|_, _| 0, |len, w, &child| len.max(w + child));
+    assert_eq!(longest[0], 6);
+    assert_eq!(*longest.last().unwrap(), 0);
+    // The two nodes of the first rung each have two rungs remaining below them.
+    assert_eq!(&longest[1..3], &[4, 4]);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "graphml")]
+
+extern crate daggy;
+
+use daggy::graphml::{AttrValue, Attributes, GraphMlError};
+use daggy::Dag;
+
+fn attrs(pairs: Vec<(&str, AttrValue)>) -> Attributes {
+    pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+}
+
+#[test]
+fn round_trip() {
+    let mut dag = Dag::<(&str, i64), (bool, f32)>::new();
+    let root = dag.add_node(("root <&>", 1));
+    let (_, a) = dag.add_child(root, (true, 0.5), ("a", -2));
+    let (_, b) = dag.add_child(root, (false, f32::INFINITY), ("b", i64::MAX));
+    dag.add_edge(a, b, (true, -1.25)).unwrap();
+
+    let xml = dag.to_graphml(
+        |_, &(label, size)| attrs(vec![("label", label.into()), ("size", size.into())]),
+        |_, &(on, weight)| attrs(vec![("on", on.into()), ("weight", weight.into())]),
+    );
+    assert!(xml.contains("<key id=\"d1\" for=\"node\" attr.name=\"size\" attr.type=\"long\"/>"));
+    assert!(xml.contains("<graph id=\"G\" edgedefault=\"directed\">"));
+    assert!(xml.contains("root &lt;&amp;&gt;"));
+
+    let parsed = Dag::from_graphml(
+        &xml,
+        |id, a| (id.to_string(), a.clone()),
+        |a| (a["on"].clone(), a["weight"].clone()),
+    )
+    .unwrap();
+    assert_eq!(parsed.node_count(), 3);
+    assert_eq!(parsed[b].0, "n2");
+    assert_eq!(parsed[root].1["label"], AttrValue::from("root <&>"));
+    assert_eq!(parsed[b].1["size"], AttrValue::Long(i64::MAX));
+    let edges: Vec<_> = parsed
+        .raw_edges()
+        .iter()
+        .map(|e| e.weight.clone())
+        .collect();
+    assert_eq!(
+        edges,
+        vec![
+            (AttrValue::Boolean(true), AttrValue::Float(0.5)),
+            (AttrValue::Boolean(false), AttrValue::Float(f32::INFINITY)),
+            (AttrValue::Boolean(true), AttrValue::Float(-1.25)),
+        ]
+    );
+}
+
+#[test]
+fn read_defaults_and_extensions() {
+    let xml = r##"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns" xmlns:y="http://www.yworks.com/xml/graphml">
+  <key id="color" for="node" attr.name="color" attr.type="string">
+    <default>yellow</default>
+  </key>
+  <key id="g" for="node" yfiles.type="nodegraphics"/>
+  <key id="w" for="edge" attr.name="weight" attr.type="double"/>
+  <graph id="G" edgedefault="directed">
+    <desc>An example.</desc>
+    <edge source="b" target="a"><data key="w">2.5</data></edge>
+    <node id="a"><data key="g"><y:ShapeNode><y:Fill color="#FFCC00"/></y:ShapeNode></data></node>
+    <node id="b"><data key="color">green</data></node>
+  </graph>
+</graphml>"##;
+    let dag = Dag::from_graphml(
+        xml,
+        |id, a| format!("{}:{:?}", id, a["color"]),
+        |a| a.get("weight").cloned(),
+    )
+    .unwrap();
+    let nodes: Vec<_> = dag.raw_nodes().iter().map(|n| n.weight.as_str()).collect();
+    assert_eq!(nodes, ["a:String(\"yellow\")", "b:String(\"green\")"]);
+    assert_eq!(dag.raw_edges()[0].weight, Some(AttrValue::Double(2.5)));
+}
+
+#[test]
+fn read_errors() {
+    let read = |body: &str| {
+        let xml = format!("<graphml>{}</graphml>", body);
+        Dag::from_graphml(&xml, |id, _| id.to_string(), |_| ()).map(|_| ())
+    };
+    let undirected = read(r#"<graph edgedefault="undirected"><node id="a"/></graph>"#);
+    assert_eq!(undirected, Err(GraphMlError::Undirected));
+    let cyclic = read(
+        r#"<graph edgedefault="directed"><node id="a"/><node id="b"/>
+        <edge source="a" target="b"/><edge source="b" target="a"/></graph>"#,
+    );
+    assert_eq!(
+        cyclic,
+        Err(GraphMlError::WouldCycle {
+            source: "b".into(),
+            target: "a".into(),
+        })
+    );
+    let missing = read(r#"<graph edgedefault="directed"><edge source="a" target="b"/></graph>"#);
+    assert_eq!(missing, Err(GraphMlError::UnknownNode("a".into())));
+    let invalid = read(
+        r#"<key id="k" for="node" attr.name="n" attr.type="int"/>
+        <graph edgedefault="directed"><node id="a"><data key="k">x</data></node></graph>"#,
+    );
+    assert_eq!(
+        invalid,
+        Err(GraphMlError::InvalidValue {
+            key: "k".into(),
+            value: "x".into(),
+        })
+    );
+}
@@ -0,0 +1,117 @@
+extern crate daggy;
+
+use daggy::history::HistoryDag;
+use daggy::{Dag, Walker};
+
+type Adjacency = Vec<(Vec<usize>, Vec<usize>)>;
+type Snapshot = (Vec<&'static str>, Vec<(usize, usize, u32)>, Adjacency);
+
+// The children and parents of each node in the order in which they are visited.
+fn adjacency(dag: &Dag<&'static str, u32>) -> Adjacency {
+    dag.graph()
+        .node_indices()
+        .map(|n| {
+            let children = dag.children(n).iter(dag).map(|(e, _)| e.index()).collect();
+            let parents = dag.parents(n).iter(dag).map(|(e, _)| e.index()).collect();
+            (children, parents)
+        })
+        .collect()
+}
+
+fn snapshot(dag: &Dag<&'static str, u32>) -> Snapshot {
+    let nodes = dag.raw_nodes().iter().map(|n| n.weight).collect();
+    let edges = dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    (nodes, edges, adjacency(dag))
+}
+
+#[test]
+fn undo_redo_checkpoints() {
+    let mut dag = HistoryDag::<&str, u32>::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    let (_, c) = dag.add_child(a, 1, "c");
+    dag.add_edge(b, c, 2).unwrap();
+    dag.checkpoint();
+    let first = snapshot(dag.dag());
+
+    // Removing `a` moves `c` into its index.
+    dag.remove_node(a).unwrap();
+    let c = a;
+    *dag.node_weight_mut(b).unwrap() = "B";
+    dag.update_edge(b, c, 3).unwrap();
+    let (_, d) = dag.add_parent(b, 4, "d");
+    dag.add_edge(d, c, 5).unwrap();
+    dag.checkpoint();
+    let second = snapshot(dag.dag());
+
+    assert!(dag.undo());
+    assert_eq!(snapshot(dag.dag()), first);
+    assert!(dag.redo());
+    assert_eq!(snapshot(dag.dag()), second);
+    assert!(dag.undo());
+    assert!(dag.undo());
+    assert_eq!(dag.node_count(), 0);
+    assert!(!dag.undo());
+    assert!(dag.redo());
+    assert_eq!(snapshot(dag.dag()), first);
+
+    // A new edit discards the redo history.
+    dag.set_node_weight(a, "A");
+    assert!(!dag.can_redo());
+    assert!(dag.undo());
+    assert_eq!(snapshot(dag.dag()), first);
+}
+
+#[test]
+fn undo_redo_restores_neighbour_order() {
+    let mut dag = HistoryDag::<&str, u32>::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    dag.add_child(a, 1, "c");
+    let (_, d) = dag.add_child(a, 2, "d");
+    dag.add_edge(b, d, 3).unwrap();
+    dag.checkpoint();
+    let before = snapshot(dag.dag());
+    assert_eq!(adjacency(dag.dag())[a.index()].0, vec![2, 1, 0]);
+
+    dag.remove_node(b).unwrap();
+    dag.add_edge(a, b, 4).unwrap();
+    dag.checkpoint();
+    let after = snapshot(dag.dag());
+
+    assert!(dag.undo());
+    assert_eq!(snapshot(dag.dag()), before);
+    assert!(dag.redo());
+    assert_eq!(snapshot(dag.dag()), after);
+    assert!(dag.undo());
+    assert_eq!(snapshot(dag.dag()), before);
+}
+
+#[test]
+fn cycles_are_not_recorded() {
+    let mut dag = HistoryDag::<&str, u32>::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    dag.checkpoint();
+    assert!(dag.add_edge(b, a, 1).is_err());
+    assert!(dag.update_edge(b, a, 1).is_err());
+    assert!(dag.undo());
+    assert_eq!(dag.node_count(), 0);
+}
+
+#[test]
+fn bounded_history() {
+    let mut dag = HistoryDag::<&str, u32>::new().with_max_history(2);
+    for name in &["a", "b", "c", "d"] {
+        dag.add_node(name);
+        dag.checkpoint();
+    }
+    assert!(dag.undo());
+    assert!(dag.undo());
+    assert!(!dag.undo());
+    assert_eq!(dag.node_count(), 2);
+}
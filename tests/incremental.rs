@@ -0,0 +1,54 @@
+extern crate daggy;
+
+use daggy::incremental::{Incremental, NodeState};
+
+fn sum(_: daggy::NodeIndex, weight: &i32, parents: &[(&(), &i32)]) -> i32 {
+    weight + parents.iter().map(|(_, v)| **v).sum::<i32>()
+}
+
+#[test]
+fn recompute_dirty_nodes() {
+    let mut inc = Incremental::<i32, (), i32>::new();
+    let a = inc.add_node(1);
+    let (_, b) = inc.add_child(a, (), 2);
+    let (_, c) = inc.add_child(b, (), 3);
+    let d = inc.add_node(4);
+    inc.add_edge(d, c, ()).unwrap();
+    assert_eq!(inc.recompute(sum), 4);
+    assert_eq!(inc.value(c), Some(&10));
+    assert_eq!(inc.recompute(sum), 0);
+
+    *inc.node_weight_mut(a).unwrap() = 5;
+    assert_eq!(inc.state(a), Some(NodeState::Dirty));
+    assert_eq!(inc.state(c), Some(NodeState::Stale));
+    assert_eq!(inc.state(d), Some(NodeState::Clean));
+    assert_eq!(inc.recompute(sum), 3);
+    assert_eq!(inc.value(c), Some(&14));
+
+    inc.remove_node(d);
+    assert_eq!(inc.recompute(sum), 1);
+    assert_eq!(inc.value(c), Some(&10));
+}
+
+#[test]
+fn early_cutoff() {
+    let mut inc = Incremental::<i32, (), bool>::new();
+    let a = inc.add_node(1);
+    let (_, b) = inc.add_child(a, (), 0);
+    let (_, c) = inc.add_child(b, (), 0);
+    let mut evaluated = vec![];
+    let mut is_positive = |n, w: &i32, parents: &[(&(), &bool)]| {
+        evaluated.push(n);
+        match parents.first() {
+            Some((_, v)) => **v,
+            None => *w > 0,
+        }
+    };
+    inc.recompute(&mut is_positive);
+    *inc.node_weight_mut(a).unwrap() = 2;
+    inc.recompute(&mut is_positive);
+    // `a` was re-evaluated but its value did not change, so neither `b` nor `c` were.
+    assert_eq!(evaluated, vec![a, b, c, a]);
+    assert_eq!(inc.state(c), Some(NodeState::Clean));
+    assert_eq!(inc.value(c), Some(&true));
+}
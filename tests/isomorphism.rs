@@ -0,0 +1,101 @@
+extern crate daggy;
+
+use daggy::{Dag, NodeIndex};
+
+type Snapshot = (Vec<u8>, Vec<(usize, usize, u8)>);
+
+fn snapshot(dag: &Dag<u8, u8>) -> Snapshot {
+    let nodes = dag.raw_nodes().iter().map(|n| n.weight).collect();
+    let edges = dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    (nodes, edges)
+}
+
+// Rebuild the dag with its nodes inserted in a scrambled order and its edges reversed.
+fn scrambled(dag: &Dag<u8, u8>) -> Dag<u8, u8> {
+    let n = dag.node_count();
+    let order: Vec<usize> = (0..n).map(|i| (i * 7 + 3) % n).collect();
+    let mut position = vec![0; n];
+    let mut out = Dag::new();
+    for (p, &i) in order.iter().enumerate() {
+        position[i] = p;
+        out.add_node(dag[NodeIndex::new(i)]);
+    }
+    for e in dag.raw_edges().iter().rev() {
+        let a = NodeIndex::new(position[e.source().index()]);
+        let b = NodeIndex::new(position[e.target().index()]);
+        out.add_edge(a, b, e.weight).unwrap();
+    }
+    out
+}
+
+// Two identical branches below a root, each fanning out to identical leaves.
+fn symmetric() -> Dag<u8, u8> {
+    let mut dag = Dag::new();
+    let root = dag.add_node(0);
+    let sink = dag.add_node(9);
+    for _ in 0..2 {
+        let (_, branch) = dag.add_child(root, 0, 1);
+        for _ in 0..3 {
+            let (_, leaf) = dag.add_child(branch, 1, 2);
+            dag.add_edge(leaf, sink, 2).unwrap();
+        }
+    }
+    dag
+}
+
+#[test]
+fn isomorphic_up_to_relabelling() {
+    let dag = symmetric();
+    let other = scrambled(&dag);
+    assert_ne!(snapshot(&dag), snapshot(&other));
+    assert!(dag.is_isomorphic_to(&other, |a, b| a == b, |a, b| a == b));
+    assert_eq!(
+        snapshot(&dag.canonical_form()),
+        snapshot(&other.canonical_form())
+    );
+}
+
+#[test]
+fn not_isomorphic() {
+    let dag = symmetric();
+    let mut other = scrambled(&dag);
+    // Change the weight of a single leaf.
+    let leaf = other
+        .raw_edges()
+        .iter()
+        .find(|e| e.weight == 2)
+        .map(|e| e.source())
+        .unwrap();
+    other[leaf] = 3;
+    assert!(!dag.is_isomorphic_to(&other, |a, b| a == b, |a, b| a == b));
+    assert!(dag.is_isomorphic_to(&other, |_, _| true, |a, b| a == b));
+    assert_ne!(
+        snapshot(&dag.canonical_form()),
+        snapshot(&other.canonical_form())
+    );
+
+    let mut other = scrambled(&dag);
+    let e = daggy::EdgeIndex::new(0);
+    other[e] += 1;
+    assert!(!dag.is_isomorphic_to(&other, |a, b| a == b, |a, b| a == b));
+}
+
+#[test]
+fn canonical_form_of_unweighted_structure() {
+    // Nodes that can only be told apart by their position in the structure.
+    let mut dag = Dag::<u8, u8>::new();
+    let nodes: Vec<_> = (0..6).map(|_| dag.add_node(0)).collect();
+    for &(a, b) in &[(0, 1), (1, 2), (0, 3), (3, 2), (4, 5), (5, 2)] {
+        dag.add_edge(nodes[a], nodes[b], 0).unwrap();
+    }
+    let other = scrambled(&dag);
+    assert!(dag.is_isomorphic_to(&other, |a, b| a == b, |a, b| a == b));
+    assert_eq!(
+        snapshot(&dag.canonical_form()),
+        snapshot(&other.canonical_form())
+    );
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "jgf")]
+
+extern crate daggy;
+
+use daggy::jgf::JgfError;
+use daggy::Dag;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Task {
+    name: String,
+    cost: u32,
+}
+
+#[test]
+fn round_trip_preserves_ids() {
+    let task = |name: &str, cost| Task {
+        name: name.to_string(),
+        cost,
+    };
+    let mut dag = Dag::<Task, Option<String>>::new();
+    let fetch = dag.add_node(task("fetch", 1));
+    let (_, build) = dag.add_child(fetch, Some("src".to_string()), task("build", 5));
+    let (_, test) = dag.add_child(build, None, task("test", 3));
+    dag.add_edge(fetch, test, Some("fixtures".to_string()))
+        .unwrap();
+
+    let json = dag.to_jgf(|_, t| t.name.clone()).unwrap();
+    let (parsed, ids) = Dag::<Task, Option<String>>::from_jgf(&json).unwrap();
+    assert_eq!(ids.len(), 3);
+    assert_eq!(ids.id(build), "build");
+    assert_eq!(ids.index("test"), Some(test));
+    for (n, id) in ids.iter() {
+        assert_eq!(parsed[n], dag[n]);
+        assert_eq!(parsed[n].name, id);
+    }
+    let edges = |d: &Dag<Task, Option<String>>| -> Vec<_> {
+        d.raw_edges()
+            .iter()
+            .map(|e| (e.source(), e.target(), e.weight.clone()))
+            .collect()
+    };
+    assert_eq!(edges(&parsed), edges(&dag));
+
+    let err = dag.to_jgf(|_, _| "same".to_string()).unwrap_err();
+    assert!(matches!(err, JgfError::DuplicateNode(ref id) if id == "same"));
+}
+
+#[test]
+fn reads_node_lists_and_graph_lists() {
+    let json = r#"{
+        "graphs": [{
+            "label": "v1",
+            "nodes": [
+                { "id": "b", "label": "B" },
+                { "id": "a", "metadata": null }
+            ],
+            "edges": [{ "source": "a", "target": "b", "relation": "before" }]
+        }]
+    }"#;
+    let (dag, ids) = Dag::<(), ()>::from_jgf(json).unwrap();
+    assert_eq!(dag.node_count(), 2);
+    assert_eq!(ids.iter().map(|(_, id)| id).collect::<Vec<_>>(), ["b", "a"]);
+    let edge = &dag.raw_edges()[0];
+    assert_eq!(
+        (edge.source(), edge.target()),
+        (ids.index("a").unwrap(), ids.index("b").unwrap())
+    );
+}
+
+#[test]
+fn rejects_invalid_documents() {
+    let read = |json: &str| Dag::<u32, ()>::from_jgf(json).unwrap_err();
+    let nodes = r#""nodes": { "a": { "metadata": 1 }, "b": { "metadata": 2 } }"#;
+
+    let undirected = format!(r#"{{ "graph": {{ "directed": false, {} }} }}"#, nodes);
+    assert!(matches!(read(&undirected), JgfError::Undirected));
+    let edge = format!(
+        r#"{{ "graph": {{ {}, "edges": [{{ "source": "a", "target": "b", "directed": false }}] }} }}"#,
+        nodes
+    );
+    assert!(matches!(read(&edge), JgfError::Undirected));
+
+    let cycle = format!(
+        r#"{{ "graph": {{ {}, "edges": [{{ "source": "a", "target": "b" }}, {{ "source": "b", "target": "a" }}] }} }}"#,
+        nodes
+    );
+    match read(&cycle) {
+        JgfError::WouldCycle { source, target } => {
+            assert_eq!((&source[..], &target[..]), ("b", "a"))
+        }
+        other => panic!("unexpected error: {:?}", other),
+    }
+
+    let unknown = format!(
+        r#"{{ "graph": {{ {}, "edges": [{{ "source": "a", "target": "c" }}] }} }}"#,
+        nodes
+    );
+    assert!(matches!(read(&unknown), JgfError::UnknownNode(ref id) if id == "c"));
+    let metadata = r#"{ "graph": { "nodes": { "a": { "metadata": "one" } } } }"#;
+    assert!(matches!(read(metadata), JgfError::InvalidNodeMetadata { ref id, .. } if id == "a"));
+    assert!(matches!(
+        read(r#"{ "graphs": [] }"#),
+        JgfError::MissingGraph
+    ));
+    let both = r#"{ "graph": { "nodes": {} }, "graphs": [{ "nodes": {} }] }"#;
+    assert!(matches!(read(both), JgfError::MultipleGraphs(2)));
+    assert!(matches!(read("[]"), JgfError::Json(_)));
+}
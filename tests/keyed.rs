@@ -0,0 +1,54 @@
+extern crate daggy;
+
+use daggy::keyed::KeyedDag;
+
+#[test]
+fn add_edge_by_key_creates_endpoints() {
+    let mut dag = KeyedDag::<&str, u32, &str>::new();
+    dag.add_node("a", 1);
+    dag.add_edge_by_key("a", "b", "a->b").unwrap();
+    dag.add_edge_by_key("b", "c", "b->c").unwrap();
+
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.edge_count(), 2);
+    assert_eq!(dag.node_weight("a"), Some(&1));
+    assert_eq!(dag.node_weight("c"), Some(&0));
+    let e = dag.find_edge("b", "c").unwrap();
+    assert_eq!(dag.edge_weight(e), Some(&"b->c"));
+}
+
+#[test]
+fn add_edge_by_key_cycle() {
+    let mut dag = KeyedDag::<&str, u32, ()>::new();
+    dag.add_edge_by_key("a", "b", ()).unwrap();
+    dag.add_edge_by_key("b", "c", ()).unwrap();
+
+    assert!(dag.add_edge_by_key("c", "a", ()).is_err());
+    assert!(dag.add_edge_by_key("d", "d", ()).is_err());
+    assert!(!dag.contains_key("d"));
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.edge_count(), 2);
+}
+
+#[test]
+fn remove_node_keeps_keys_consistent() {
+    let mut dag = KeyedDag::<String, u32, ()>::new();
+    for (i, key) in ["a", "b", "c", "d"].iter().enumerate() {
+        dag.add_node(key.to_string(), i as u32);
+    }
+    dag.add_edge_by_key("a".into(), "d".into(), ()).unwrap();
+
+    // Removing "b" swaps "d" into its index.
+    let b = dag.node_index("b").unwrap();
+    assert_eq!(dag.remove_node("b"), Some(1));
+    assert_eq!(dag.node_index("d"), Some(b));
+    assert_eq!(dag.key(b).map(|k| k.as_str()), Some("d"));
+
+    for key in dag.keys() {
+        let n = dag.node_index(key.as_str()).unwrap();
+        assert_eq!(dag.key(n), Some(key));
+    }
+    assert_eq!(dag.node_weight("d"), Some(&3));
+    assert!(dag.find_edge("a", "d").is_some());
+    assert_eq!(dag.remove_node("b"), None);
+}
@@ -0,0 +1,108 @@
+extern crate daggy;
+
+use daggy::merge::{merge, MergeConflict};
+use daggy::{Dag, NodeIndex};
+
+type Node = (&'static str, u32);
+
+fn key(node: &Node) -> &'static str {
+    node.0
+}
+
+fn node(dag: &Dag<Node, ()>, key: &str) -> NodeIndex {
+    let i = dag.raw_nodes().iter().position(|n| n.weight.0 == key);
+    NodeIndex::new(i.unwrap())
+}
+
+// a -> b -> c, a -> d
+fn base() -> Dag<Node, ()> {
+    let mut dag = Dag::new();
+    let a = dag.add_node(("a", 0));
+    let (_, b) = dag.add_child(a, (), ("b", 0));
+    dag.add_child(b, (), ("c", 0));
+    dag.add_child(a, (), ("d", 0));
+    dag
+}
+
+#[test]
+fn merge_independent_changes() {
+    let base = base();
+    let mut ours = base.clone();
+    let c = node(&ours, "c");
+    ours.add_child(c, (), ("e", 0));
+    ours[c].1 = 1;
+    let mut theirs = base.clone();
+    theirs.remove_node(node(&theirs, "b"));
+    let d = node(&theirs, "d");
+    theirs.add_child(d, (), ("f", 0));
+    // An identical change on both sides is applied once.
+    theirs.add_child(d, (), ("g", 0));
+    ours.add_child(node(&ours, "d"), (), ("g", 0));
+
+    let merged = merge(&base, &ours, &theirs, key).unwrap();
+    let mut names: Vec<_> = merged.raw_nodes().iter().map(|n| n.weight).collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec![("a", 0), ("c", 1), ("d", 0), ("e", 0), ("f", 0), ("g", 0)]
+    );
+    assert_eq!(merged.edge_count(), 4);
+}
+
+#[test]
+fn merge_conflicts() {
+    let base = base();
+    let mut ours = base.clone();
+    let mut theirs = base.clone();
+    let (b, c, d) = (node(&base, "b"), node(&base, "c"), node(&base, "d"));
+    ours[b].1 = 1;
+    theirs[b].1 = 2;
+    ours[d].1 = 1;
+    ours.add_child(c, (), ("e", 0));
+    theirs.remove_node(d);
+    theirs.remove_node(node(&theirs, "c"));
+
+    let conflicts = merge(&base, &ours, &theirs, key).unwrap_err();
+    assert!(conflicts.contains(&MergeConflict::BothModified("b")));
+    assert!(conflicts.contains(&MergeConflict::ModifiedAndRemoved("d")));
+    assert!(conflicts.contains(&MergeConflict::RemovedAndConnected("c")));
+    assert_eq!(conflicts.len(), 3);
+}
+
+#[test]
+fn merge_joint_cycle() {
+    let base = base();
+    let mut ours = base.clone();
+    let mut theirs = base.clone();
+    let (c, d) = (node(&base, "c"), node(&base, "d"));
+    ours.add_edge(c, d, ()).unwrap();
+    theirs.add_edge(d, c, ()).unwrap();
+
+    let conflicts = merge(&base, &ours, &theirs, key).unwrap_err();
+    assert_eq!(conflicts, vec![MergeConflict::Cycle(vec!["c", "d"])]);
+}
+
+#[test]
+fn merge_parallel_edges() {
+    let mut base = Dag::<Node, &str>::new();
+    let a = base.add_node(("a", 0));
+    let b = base.add_node(("b", 0));
+    let x = base.add_edge(a, b, "x").unwrap();
+    let y = base.add_edge(a, b, "y").unwrap();
+
+    // Each side removes a different one of the two parallel edges.
+    let mut ours = base.clone();
+    ours.remove_edge(x);
+    let mut theirs = base.clone();
+    theirs.remove_edge(y);
+    let merged = merge(&base, &ours, &theirs, key).unwrap();
+    assert_eq!(merged.node_count(), 2);
+    assert_eq!(merged.edge_count(), 0);
+
+    // Modifying one while removing the other is not a conflict.
+    let mut ours = base.clone();
+    ours[x] = "z";
+    let merged = merge(&base, &ours, &theirs, key).unwrap();
+    let weights: Vec<_> = merged.raw_edges().iter().map(|e| e.weight).collect();
+    assert_eq!(weights, vec!["z"]);
+}
@@ -0,0 +1,91 @@
+extern crate daggy;
+
+use daggy::merkle::ChildOrder;
+use daggy::Dag;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+fn hash<T: Hash>(value: T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hashes(dag: &Dag<&str, u8>, order: ChildOrder) -> Vec<u64> {
+    dag.merkle_hashes(
+        order,
+        |w, children| hash((w, children)),
+        |e, h| hash((e, h)),
+    )
+}
+
+fn digest(dag: &Dag<&str, u8>, order: ChildOrder) -> u64 {
+    dag.merkle_digest(
+        order,
+        |w, children| hash((w, children)),
+        |e, h| hash((e, h)),
+        |roots| hash(roots),
+    )
+}
+
+#[test]
+fn merkle_hashes_ignore_indices() {
+    // a -> b, a -> c, b -> d, c -> d
+    let mut x = Dag::<&str, u8>::new();
+    let a = x.add_node("a");
+    let (_, b) = x.add_child(a, 0, "b");
+    let (_, c) = x.add_child(a, 1, "c");
+    let (_, d) = x.add_child(b, 2, "d");
+    x.add_edge(c, d, 3).unwrap();
+
+    // The same graph built in a different order.
+    let mut y = Dag::<&str, u8>::new();
+    let yd = y.add_node("d");
+    let (_, yc) = y.add_parent(yd, 3, "c");
+    let (_, yb) = y.add_parent(yd, 2, "b");
+    let ya = y.add_node("a");
+    y.add_edge(ya, yc, 1).unwrap();
+    y.add_edge(ya, yb, 0).unwrap();
+
+    let (hx, hy) = (
+        hashes(&x, ChildOrder::Unordered),
+        hashes(&y, ChildOrder::Unordered),
+    );
+    assert_eq!(hx[a.index()], hy[ya.index()]);
+    assert_eq!(hx[d.index()], hy[yd.index()]);
+    assert_eq!(
+        digest(&x, ChildOrder::Unordered),
+        digest(&y, ChildOrder::Unordered)
+    );
+
+    // With ordered children, the order in which `a`'s edges were added matters.
+    let (hx, hy) = (
+        hashes(&x, ChildOrder::Ordered),
+        hashes(&y, ChildOrder::Ordered),
+    );
+    assert_ne!(hx[a.index()], hy[ya.index()]);
+    assert_eq!(hx[b.index()], hy[yb.index()]);
+}
+
+#[test]
+fn merkle_hashes_cover_dependencies() {
+    let mut dag = Dag::<&str, u8>::new();
+    let a = dag.add_node("a");
+    let (e, b) = dag.add_child(a, 0, "b");
+    let (_, c) = dag.add_child(b, 0, "c");
+    let other = dag.add_node("other");
+    let before = hashes(&dag, ChildOrder::Unordered);
+    let digest_before = digest(&dag, ChildOrder::Unordered);
+
+    dag[c] = "c2";
+    let after = hashes(&dag, ChildOrder::Unordered);
+    assert!((0..3).all(|i| before[i] != after[i]));
+    assert_eq!(before[other.index()], after[other.index()]);
+    assert_ne!(digest_before, digest(&dag, ChildOrder::Unordered));
+
+    dag[c] = "c";
+    dag[e] = 1;
+    let after = hashes(&dag, ChildOrder::Unordered);
+    assert_ne!(before[a.index()], after[a.index()]);
+    assert_eq!(before[b.index()], after[b.index()]);
+}
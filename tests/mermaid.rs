@@ -0,0 +1,91 @@
+extern crate daggy;
+
+use daggy::mermaid::FromMermaidError;
+use daggy::Dag;
+
+fn example() -> Dag<String, String> {
+    let mut dag = Dag::new();
+    let root = dag.add_node("root".to_string());
+    let (_, a) = dag.add_child(
+        root,
+        "uses | pipes".to_string(),
+        "a \"quoted\" #1".to_string(),
+    );
+    let (_, b) = dag.add_child(root, String::new(), "<b>\nend".to_string());
+    dag.add_child(a, "x".to_string(), "leaf".to_string());
+    dag.add_edge(b, a, String::new()).unwrap();
+    dag
+}
+
+#[test]
+fn round_trip() {
+    let dag = example();
+    let mermaid = dag.to_mermaid(|_, n| n.clone(), |_, e| e.clone());
+    assert!(mermaid.starts_with("flowchart TD\n"));
+    assert!(mermaid.contains("n1[\"a #quot;quoted#quot; #35;1\"]"));
+    assert!(mermaid.contains("n2[\"#lt;b#gt;<br>end\"]"));
+    let parsed = Dag::from_mermaid(&mermaid).unwrap();
+    let weights = |d: &Dag<String, String>| {
+        let nodes: Vec<_> = d.raw_nodes().iter().map(|n| n.weight.clone()).collect();
+        let edges: Vec<_> = d
+            .raw_edges()
+            .iter()
+            .map(|e| (e.source(), e.target(), e.weight.clone()))
+            .collect();
+        (nodes, edges)
+    };
+    assert_eq!(weights(&parsed), weights(&dag));
+}
+
+#[test]
+fn grouped() {
+    let dag = example();
+    let mermaid = dag.to_mermaid_grouped(
+        |n, _| match n.index() {
+            0 => None,
+            i => Some(format!("group {}", i % 2)),
+        },
+        |_, n| n.clone(),
+        |_, _| String::new(),
+    );
+    let expected = "flowchart TD
+    n0[\"root\"]
+    subgraph g0[\"group 1\"]
+        n1[\"a #quot;quoted#quot; #35;1\"]
+        n3[\"leaf\"]
+    end
+    subgraph g1[\"group 0\"]
+        n2[\"#lt;b#gt;<br>end\"]
+    end
+    n0 --> n1
+    n0 --> n2
+    n1 --> n3
+    n2 --> n1
+";
+    assert_eq!(mermaid, expected);
+    let parsed = Dag::from_mermaid(&mermaid).unwrap();
+    assert_eq!(parsed.node_count(), 4);
+    assert_eq!(parsed.raw_nodes()[2].weight, "leaf");
+}
+
+#[test]
+fn parse_errors() {
+    let cyclic = Dag::from_mermaid("graph LR\n    a --> b\n    b --> a\n");
+    assert_eq!(
+        cyclic.unwrap_err(),
+        FromMermaidError::WouldCycle {
+            source: "b".into(),
+            target: "a".into(),
+        }
+    );
+    match Dag::from_mermaid("flowchart TD\n    a[\"A --> b\n") {
+        Err(FromMermaidError::Syntax { line: 2, .. }) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    let unclosed = "flowchart TD\n    subgraph g0\n    subgraph g1\n    a\n    end\n";
+    match Dag::from_mermaid(unclosed) {
+        Err(FromMermaidError::Syntax { line: 2, .. }) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(Dag::from_mermaid("sequenceDiagram\n").is_err());
+}
@@ -0,0 +1,89 @@
+extern crate daggy;
+
+use daggy::observed::{DagObserver, ObservedDag};
+use daggy::{EdgeIndex, NodeIndex};
+
+#[derive(Debug, Default, PartialEq)]
+struct Log(Vec<String>);
+
+impl DagObserver<&'static str, u32> for Log {
+    fn on_node_added(&mut self, node: NodeIndex, weight: &&'static str) {
+        self.0.push(format!("+n{} {}", node.index(), weight));
+    }
+
+    fn on_edge_added(&mut self, edge: EdgeIndex, a: NodeIndex, b: NodeIndex, weight: &u32) {
+        let (e, a, b) = (edge.index(), a.index(), b.index());
+        self.0.push(format!("+e{} {}->{} {}", e, a, b, weight));
+    }
+
+    fn on_edge_removed(
+        &mut self,
+        edge: EdgeIndex,
+        _: NodeIndex,
+        _: NodeIndex,
+        _: &u32,
+        swapped_from: Option<EdgeIndex>,
+    ) {
+        let from = swapped_from.map(|e| e.index());
+        self.0.push(format!("-e{} {:?}", edge.index(), from));
+    }
+
+    fn on_node_removed(&mut self, node: NodeIndex, _: &&str, swapped_from: Option<NodeIndex>) {
+        let from = swapped_from.map(|n| n.index());
+        self.0.push(format!("-n{} {:?}", node.index(), from));
+    }
+
+    fn on_edge_weight_changed(&mut self, edge: EdgeIndex, weight: &u32) {
+        self.0.push(format!("~e{} {}", edge.index(), weight));
+    }
+}
+
+#[test]
+fn observe_mutations() {
+    let mut dag = ObservedDag::new(Log::default());
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    let (_, c) = dag.add_parent(b, 1, "c");
+    assert!(dag.add_edge(b, a, 2).is_err());
+    dag.update_edge(a, b, 3).unwrap();
+    dag.update_edge(c, a, 4).unwrap();
+    dag.remove_node(a);
+    let log = dag.observer().0.clone();
+    assert_eq!(
+        log,
+        vec![
+            "+n0 a",
+            "+n1 b",
+            "+e0 0->1 0",
+            "+n2 c",
+            "+e1 2->1 1",
+            "~e0 3",
+            "+e2 2->0 4",
+            "-e0 Some(2)",
+            "-e0 Some(1)",
+            "-n0 Some(2)",
+        ]
+    );
+}
+
+#[test]
+fn observe_batches() {
+    let edges = vec![(0, 1, 0), (1, 2, 1)];
+    let mut dag = ObservedDag::<_, _, _>::from_edges(edges, Log::default()).unwrap();
+    let (n0, n2) = (NodeIndex::new(0), NodeIndex::new(2));
+    dag.add_edges(vec![(n0, n2, 2), (n0, n2, 3)]).unwrap();
+    assert!(dag.add_edges(vec![(n2, n0, 4)]).is_err());
+    let (_, log) = dag.into_parts();
+    assert_eq!(
+        log.0,
+        vec![
+            "+n0 ",
+            "+n1 ",
+            "+n2 ",
+            "+e0 0->1 0",
+            "+e1 1->2 1",
+            "+e2 0->2 2",
+            "+e3 0->2 3",
+        ]
+    );
+}
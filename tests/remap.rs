@@ -0,0 +1,89 @@
+extern crate daggy;
+
+use daggy::{Dag, EdgeIndex, NodeIndex};
+
+#[test]
+fn remove_node_with_remap() {
+    let mut dag = Dag::<u32, ()>::new();
+    let a = dag.add_node(0);
+    let b = dag.add_node(1);
+    let c = dag.add_node(2);
+    dag.add_edge(a, c, ()).unwrap();
+
+    assert_eq!(dag.remove_node_with_remap(a), Some((0, Some((c, a)))));
+    assert_eq!(dag[a], 2);
+    assert_eq!(dag.remove_node_with_remap(b), Some((1, None)));
+    assert_eq!(dag.remove_node_with_remap(b), None);
+    assert_eq!(dag.node_count(), 1);
+}
+
+#[test]
+fn remove_edge_with_remap() {
+    let mut dag = Dag::<(), u32>::new();
+    let root = dag.add_node(());
+    let (e0, _) = dag.add_child(root, 0, ());
+    let (e1, _) = dag.add_child(root, 1, ());
+    let (e2, _) = dag.add_child(root, 2, ());
+
+    assert_eq!(dag.remove_edge_with_remap(e0), Some((0, Some((e2, e0)))));
+    assert_eq!(dag[e0], 2);
+    assert_eq!(dag.remove_edge_with_remap(e1), Some((1, None)));
+    assert_eq!(dag.remove_edge_with_remap(EdgeIndex::new(5)), None);
+}
+
+#[test]
+fn retain_nodes() {
+    let mut dag = Dag::<u32, u32>::new();
+    let nodes: Vec<_> = (0..6).map(|i| dag.add_node(i)).collect();
+    for i in 0..5 {
+        dag.add_edge(nodes[i], nodes[i + 1], i as u32).unwrap();
+    }
+    dag.add_edge(nodes[0], nodes[5], 10).unwrap();
+
+    let remap = dag.retain_nodes(|mut dag, n| {
+        dag[n] *= 10;
+        dag[n] % 20 == 0
+    });
+
+    let expected: Vec<_> = [Some(0), None, Some(1), None, Some(2), None]
+        .iter()
+        .map(|i| i.map(NodeIndex::new))
+        .collect();
+    assert_eq!(remap, expected);
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.edge_count(), 0);
+    let weights: Vec<_> = dag.raw_nodes().iter().map(|n| n.weight).collect();
+    assert_eq!(weights, vec![0, 20, 40]);
+
+    let mut dag = Dag::<u32, u32>::new();
+    let a = dag.add_node(0);
+    let (_, b) = dag.add_child(a, 0, 1);
+    let (_, c) = dag.add_child(b, 1, 2);
+    dag.add_edge(a, c, 2).unwrap();
+    let remap = dag.retain_nodes(|_, n| n != b);
+    assert_eq!(remap, vec![Some(a), None, Some(NodeIndex::new(1))]);
+    assert_eq!(dag.edge_count(), 1);
+    assert_eq!(dag.find_edge(a, NodeIndex::new(1)), Some(EdgeIndex::new(0)));
+    assert_eq!(dag[EdgeIndex::new(0)], 2);
+}
+
+#[test]
+fn retain_edges() {
+    let mut dag = Dag::<u32, u32>::new();
+    let a = dag.add_node(0);
+    let b = dag.add_node(1);
+    let c = dag.add_node(2);
+    dag.add_edge(a, b, 0).unwrap();
+    dag.add_edge(b, c, 1).unwrap();
+    dag.add_edge(a, c, 2).unwrap();
+
+    let remap = dag.retain_edges(|dag, e| dag[e] != 1);
+    let expected: Vec<_> = [Some(0), None, Some(1)]
+        .iter()
+        .map(|i| i.map(EdgeIndex::new))
+        .collect();
+    assert_eq!(remap, expected);
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.find_edge(a, c), Some(EdgeIndex::new(1)));
+    assert_eq!(dag.find_edge(b, c), None);
+}
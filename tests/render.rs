@@ -0,0 +1,74 @@
+extern crate daggy;
+
+use daggy::render::AsciiDiagram;
+use daggy::Dag;
+
+#[test]
+fn forks_merges_and_crossings() {
+    // a fans out to b, c and d, which all feed into e, while b also feeds f via a crossing lane.
+    let mut dag = Dag::<&str, ()>::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, (), "b");
+    let (_, c) = dag.add_child(a, (), "c");
+    let (_, d) = dag.add_child(a, (), "d");
+    let (_, e) = dag.add_child(c, (), "e");
+    dag.add_edge(b, e, ()).unwrap();
+    dag.add_edge(d, e, ()).unwrap();
+    let (_, f) = dag.add_child(e, (), "f");
+    dag.add_edge(a, f, ()).unwrap();
+    dag.add_edge(a, b, ()).unwrap();
+
+    let expected = "\
+*        a
+|-.-.-.
+* | | |  b
+| * | |  c
+| | * |  d
+|-'-' |
+*     |  e
+|-----'
+*        f
+";
+    assert_eq!(
+        dag.ascii_diagram(|_, n| n.to_string()),
+        AsciiDiagram::from(expected)
+    );
+}
+
+#[test]
+fn multiline_labels_and_free_lanes() {
+    let mut dag = Dag::<String, ()>::new();
+    let r1 = dag.add_node("root\none".to_string());
+    let r2 = dag.add_node("root two".to_string());
+    let (_, x) = dag.add_child(r1, (), "x".to_string());
+    dag.add_edge(r2, x, ()).unwrap();
+    dag.add_node("alone\n(no edges)".to_string());
+
+    let diagram = dag.render_ascii(|i, n| format!("{}: {}", i.index(), n));
+    assert_eq!(
+        diagram,
+        "\
+*      0: root
+|      one
+| *    1: root two
+| | *  3: alone
+| |    (no edges)
+|-'
+*      2: x
+"
+    );
+}
+
+#[test]
+fn diagram_formatting() {
+    let diagram = Dag::<(), ()>::new().ascii_diagram(|_, _| String::new());
+    assert_eq!(diagram, "");
+
+    let mut dag = Dag::<u32, ()>::new();
+    let one = dag.add_node(1);
+    dag.add_child(one, (), 2);
+    let diagram = dag.ascii_diagram(|_, n| n.to_string());
+    assert_eq!(diagram.to_string(), "*  1\n*  2\n");
+    assert_eq!(format!("{:?}", diagram), "\n*  1\n*  2\n");
+    assert_eq!(diagram.into_string(), "*  1\n*  2\n");
+}
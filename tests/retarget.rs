@@ -0,0 +1,46 @@
+extern crate daggy;
+
+use daggy::Dag;
+
+#[test]
+fn retarget_edge() {
+    let mut dag = Dag::<&str, &str>::new();
+    let a = dag.add_node("a");
+    let (ab, b) = dag.add_child(a, "a->b", "b");
+    let (bc, c) = dag.add_child(b, "b->c", "c");
+    let (_, d) = dag.add_child(a, "a->d", "d");
+
+    dag.retarget_edge(ab, d).unwrap();
+    assert_eq!(dag.edge_endpoints(ab), Some((a, d)));
+    assert_eq!(dag[ab], "a->b");
+    assert_eq!(dag.edge_endpoints(bc), Some((b, c)));
+    assert_eq!(dag.edge_count(), 3);
+
+    dag.add_edge(d, b, "d->b").unwrap();
+    let err = dag.retarget_edge(bc, a).unwrap_err();
+    assert_eq!(err.0, vec![a, d, b]);
+    assert_eq!(dag.edge_endpoints(bc), Some((b, c)));
+    assert_eq!(dag[bc], "b->c");
+}
+
+#[test]
+fn resource_edge() {
+    let mut dag = Dag::<&str, u32>::new();
+    let a = dag.add_node("a");
+    let (e0, b) = dag.add_child(a, 0, "b");
+    let (e1, c) = dag.add_child(b, 1, "c");
+    let (e2, d) = dag.add_child(c, 2, "d");
+
+    dag.resource_edge(e2, a).unwrap();
+    assert_eq!(dag.edge_endpoints(e2), Some((a, d)));
+    assert_eq!(dag.edge_endpoints(e0), Some((a, b)));
+    assert_eq!(dag.edge_endpoints(e1), Some((b, c)));
+    assert_eq!(dag[e2], 2);
+
+    let err = dag.resource_edge(e0, c).unwrap_err();
+    assert_eq!(err.0, vec![b, c]);
+    assert_eq!(dag.edge_endpoints(e0), Some((a, b)));
+
+    let err = dag.resource_edge(e0, b).unwrap_err();
+    assert_eq!(err.0, vec![b]);
+}
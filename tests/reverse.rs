@@ -0,0 +1,61 @@
+extern crate daggy;
+
+use daggy::petgraph::algo::toposort;
+use daggy::petgraph::visit::{Dfs, IntoNeighbors};
+use daggy::{Dag, Walker};
+
+fn chain() -> (Dag<&'static str, u32>, Vec<daggy::NodeIndex>) {
+    let mut dag = Dag::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    let (_, c) = dag.add_child(b, 1, "c");
+    let d = dag.add_node("d");
+    dag.add_edge(a, d, 2).unwrap();
+    (dag, vec![a, b, c, d])
+}
+
+#[test]
+fn reverse() {
+    let (mut dag, n) = chain();
+    let e = dag.find_edge(n[1], n[2]).unwrap();
+    dag.reverse();
+
+    assert_eq!(dag.edge_endpoints(e), Some((n[2], n[1])));
+    assert_eq!(dag[e], 1);
+    assert!(dag.find_edge(n[0], n[1]).is_none());
+    assert!(dag.find_edge(n[1], n[0]).is_some());
+    let parents: Vec<_> = dag.parents(n[0]).iter(&dag).map(|(_, p)| p).collect();
+    assert_eq!(parents.len(), 2);
+    // The reversed graph is still a dag, so adding the original edges back must cycle.
+    assert!(dag.add_edge(n[0], n[2], 3).is_err());
+}
+
+#[test]
+fn reversed() {
+    let (dag, n) = chain();
+    let rev = dag.reversed();
+    assert_eq!(rev.node_count(), dag.node_count());
+    assert_eq!(rev.edge_count(), dag.edge_count());
+    for edge in dag.raw_edges() {
+        let e = rev.find_edge(edge.target(), edge.source()).unwrap();
+        assert_eq!(rev[e], edge.weight);
+    }
+    assert!(dag.find_edge(n[0], n[1]).is_some());
+}
+
+#[test]
+fn as_reversed() {
+    let (dag, n) = chain();
+    let rev = dag.as_reversed();
+
+    let mut from_c: Vec<_> = Dfs::new(rev, n[2]).iter(rev).collect();
+    from_c.sort();
+    assert_eq!(from_c, vec![n[0], n[1], n[2]]);
+    assert_eq!(rev.neighbors(n[3]).collect::<Vec<_>>(), vec![n[0]]);
+
+    let order = toposort(rev, None).unwrap();
+    let pos = |x| order.iter().position(|&o| o == x).unwrap();
+    assert!(pos(n[2]) < pos(n[1]));
+    assert!(pos(n[1]) < pos(n[0]));
+    assert!(pos(n[3]) < pos(n[0]));
+}
@@ -0,0 +1,160 @@
+#![cfg(feature = "serde-1")]
+
+extern crate daggy;
+extern crate serde_json;
+
+use daggy::Dag;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Config {
+    #[serde(with = "daggy::serde::adjacency")]
+    dag: Dag<String, Option<u32>>,
+}
+
+fn parse(json: &str) -> Result<Config, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+#[test]
+fn round_trip() {
+    let mut dag = Dag::new();
+    let root = dag.add_node("root".to_string());
+    let (_, a) = dag.add_child(root, Some(1), "a".to_string());
+    let (_, b) = dag.add_child(root, None, "b".to_string());
+    dag.add_edge(a, b, Some(3)).unwrap();
+
+    let json = serde_json::to_value(&Config { dag }).unwrap();
+    let expected = serde_json::json!({
+        "dag": {
+            "0": {
+                "weight": "root",
+                "children": [{ "id": "1", "weight": 1 }, { "id": "2", "weight": null }],
+            },
+            "1": { "weight": "a", "children": [{ "id": "2", "weight": 3 }] },
+            "2": { "weight": "b" },
+        }
+    });
+    assert_eq!(json, expected);
+
+    let config: Config = serde_json::from_value(json).unwrap();
+    let edges: Vec<_> = config
+        .dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    assert_eq!(edges, vec![(0, 1, Some(1)), (0, 2, None), (1, 2, Some(3))]);
+    assert_eq!(config.dag[b], "b");
+}
+
+#[test]
+fn hand_written() {
+    // Children may be listed before they are defined, and unit-like weights may be omitted.
+    let json = r#"{ "dag": {
+        "z": { "weight": "last", "children": ["a", { "id": "m" }] },
+        "a": { "weight": "first", "children": [{ "id": "m", "weight": 5 }] },
+        "m": { "weight": "middle" }
+    } }"#;
+    let config = parse(json).unwrap();
+    let weights: Vec<_> = config
+        .dag
+        .raw_nodes()
+        .iter()
+        .map(|n| &n.weight[..])
+        .collect();
+    assert_eq!(weights, ["last", "first", "middle"]);
+    assert_eq!(config.dag.raw_edges()[0].weight, None);
+    assert_eq!(config.dag.raw_edges()[1].weight, None);
+    assert_eq!(config.dag.raw_edges()[2].weight, Some(5));
+}
+
+#[test]
+fn errors_name_ids() {
+    let unknown = parse(r#"{ "dag": { "a": { "weight": "A", "children": ["nope"] } } }"#);
+    assert!(unknown
+        .unwrap_err()
+        .contains(r#"node "a" has unknown child "nope""#));
+
+    let cyclic = parse(
+        r#"{ "dag": {
+            "a": { "weight": "A", "children": ["b"] },
+            "b": { "weight": "B", "children": ["c"] },
+            "c": { "weight": "C", "children": ["a"] }
+        } }"#,
+    );
+    let err = cyclic.unwrap_err();
+    assert!(
+        err.contains(r#"cycle: "c" -> "a" -> "b" -> "c""#),
+        "{}",
+        err
+    );
+
+    let missing = parse(r#"{ "dag": { "a": { "children": [] } } }"#);
+    assert!(missing
+        .unwrap_err()
+        .contains(r#"node weight for "a" is missing"#));
+
+    let duplicate = parse(r#"{ "dag": { "a": { "weight": "A" }, "a": { "weight": "B" } } }"#);
+    assert!(duplicate.unwrap_err().contains(r#"duplicate node "a""#));
+}
+
+#[test]
+fn rejects_unknown_fields() {
+    let node =
+        parse(r#"{ "dag": { "a": { "weight": "A", "chidren": ["b"] }, "b": { "weight": "B" } } }"#);
+    assert!(node.unwrap_err().contains("unknown field `chidren`"));
+
+    let child = parse(
+        r#"{ "dag": { "a": { "weight": "A", "children": [{ "id": "b", "wieght": 1 }] }, "b": { "weight": "B" } } }"#,
+    );
+    assert!(child.is_err());
+}
+
+#[cfg(feature = "stable_dag")]
+#[test]
+fn stable_round_trip_keeps_vacant_indices() {
+    use daggy::stable_dag::StableDag;
+    use daggy::NodeIndex;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct StableConfig {
+        #[serde(with = "daggy::serde::adjacency::stable")]
+        dag: StableDag<String, u32>,
+    }
+
+    let mut dag = StableDag::new();
+    let a = dag.add_node("a".to_string());
+    let b = dag.add_node("b".to_string());
+    let c = dag.add_node("c".to_string());
+    let d = dag.add_node("d".to_string());
+    dag.add_edge(a, c, 1).unwrap();
+    dag.add_edge(c, d, 2).unwrap();
+    dag.remove_node(b);
+
+    let json = serde_json::to_value(&StableConfig { dag }).unwrap();
+    let expected = serde_json::json!({
+        "dag": {
+            "0": { "weight": "a", "children": [{ "id": "2", "weight": 1 }] },
+            "1": null,
+            "2": { "weight": "c", "children": [{ "id": "3", "weight": 2 }] },
+            "3": { "weight": "d" },
+        }
+    });
+    assert_eq!(json, expected);
+
+    let config: StableConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(config.dag.node_count(), 3);
+    assert!(config.dag.node_weight(b).is_none());
+    assert_eq!(config.dag[NodeIndex::new(3)], "d");
+    assert!(config.dag.find_edge(c, d).is_some());
+
+    let vacant_child = r#"{ "dag": { "0": { "weight": "a", "children": [{ "id": "1", "weight": 1 }] }, "1": null } }"#;
+    let err = serde_json::from_str::<StableConfig>(vacant_child).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(r#"node "0" has unknown child "1""#));
+
+    // A plain `Dag` has no vacant indices.
+    assert!(parse(r#"{ "dag": { "0": null } }"#).is_err());
+}
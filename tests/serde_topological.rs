@@ -0,0 +1,66 @@
+#![cfg(feature = "serde-1")]
+
+extern crate daggy;
+extern crate serde_json;
+
+use daggy::serde::topological::{OrderError, TopologicalDag};
+use daggy::{Dag, EdgeIndex, NodeIndex};
+use serde::{Deserialize, Serialize};
+
+fn example() -> Dag<&'static str, u32> {
+    let mut dag = Dag::new();
+    let c = dag.add_node("c");
+    let b = dag.add_node("b");
+    let a = dag.add_node("a");
+    dag.add_edge(a, b, 1).unwrap();
+    dag.add_edge(b, c, 2).unwrap();
+    dag.add_edge(a, c, 3).unwrap();
+    dag
+}
+
+#[test]
+fn preserves_chosen_order() {
+    let order = vec![NodeIndex::new(2), NodeIndex::new(1), NodeIndex::new(0)];
+    let dag = TopologicalDag::with_order(example(), order.clone()).unwrap();
+    let json = serde_json::to_value(&dag).unwrap();
+    assert_eq!(json["order"], serde_json::json!([2, 1, 0]));
+
+    let read: TopologicalDag<String, u32> = serde_json::from_value(json).unwrap();
+    assert_eq!(read.order(), &order[..]);
+    assert_eq!(read.dag().edge_count(), 3);
+    let names: Vec<_> = read.order().iter().map(|&n| &read.dag()[n][..]).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+}
+
+#[test]
+fn rejects_invalid_orders() {
+    let backward = vec![NodeIndex::new(1), NodeIndex::new(2), NodeIndex::new(0)];
+    let err = TopologicalDag::with_order(example(), backward).unwrap_err();
+    assert_eq!(err, OrderError::BackwardEdge(EdgeIndex::new(0)));
+    let repeated = vec![NodeIndex::new(2), NodeIndex::new(2), NodeIndex::new(0)];
+    let err = TopologicalDag::with_order(example(), repeated).unwrap_err();
+    assert_eq!(err, OrderError::NotAPermutation);
+
+    // A cyclic graph can never be given a valid order.
+    let mut json = serde_json::to_value(TopologicalDag::new(example())).unwrap();
+    json["graph"]["edges"][2] = serde_json::json!([0, 2, 3]);
+    let err = serde_json::from_value::<TopologicalDag<String, u32>>(json).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "edge 0 -> 2 points backward within the order"
+    );
+}
+
+#[test]
+fn with_adapter() {
+    #[derive(Serialize, Deserialize)]
+    struct Wrapper {
+        #[serde(with = "daggy::serde::topological")]
+        dag: Dag<String, u32>,
+    }
+    let dag = example().map(|_, n| n.to_string(), |_, &e| e);
+    let json = serde_json::to_string(&Wrapper { dag }).unwrap();
+    assert!(json.contains(r#""order":[2,1,0]"#), "{}", json);
+    let read: Wrapper = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.dag.node_count(), 3);
+}
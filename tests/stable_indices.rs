@@ -37,3 +37,63 @@ fn remove_edges() {
     assert_eq!(None, dag.edge_weight(e_b));
     assert_eq!(Some(&2), dag.edge_weight(e_c));
 }
+
+#[test]
+fn retain_nodes() {
+    let mut dag = StableDag::<u32, u32, u32>::new();
+    let root = dag.add_node(0);
+    let (e_a, a) = dag.add_child(root, 0, 1);
+    let (_, b) = dag.add_child(root, 1, 2);
+    let (e_c, c) = dag.add_child(a, 2, 3);
+
+    dag.retain_nodes(|dag, n| dag[n] != 2);
+
+    assert_eq!(dag.node_count(), 3);
+    assert_eq!(dag.edge_count(), 2);
+    assert!(!dag.contains_node(b));
+    assert_eq!(Some(&1), dag.node_weight(a));
+    assert_eq!(Some(&3), dag.node_weight(c));
+    assert_eq!(Some(&0), dag.edge_weight(e_a));
+    assert_eq!(Some(&2), dag.edge_weight(e_c));
+}
+
+#[test]
+fn retain_edges() {
+    let mut dag = StableDag::<u32, u32, u32>::new();
+    let root = dag.add_node(0);
+    let (e_a, _) = dag.add_child(root, 0, 1);
+    let (e_b, _) = dag.add_child(root, 1, 2);
+    let (e_c, _) = dag.add_child(root, 2, 3);
+
+    dag.retain_edges(|mut dag, e| {
+        dag[e] *= 10;
+        e != e_b
+    });
+
+    assert_eq!(dag.node_count(), 4);
+    assert_eq!(Some(&0), dag.edge_weight(e_a));
+    assert_eq!(None, dag.edge_weight(e_b));
+    assert_eq!(Some(&20), dag.edge_weight(e_c));
+}
+
+#[test]
+fn retarget_and_resource_edges() {
+    let mut dag = StableDag::<u32, u32, u32>::new();
+    let root = dag.add_node(0);
+    let (e_a, a) = dag.add_child(root, 0, 1);
+    let (e_b, b) = dag.add_child(root, 1, 2);
+    let (e_c, c) = dag.add_child(a, 2, 3);
+
+    dag.retarget_edge(e_c, b).unwrap();
+    assert_eq!(dag.edge_endpoints(e_c), Some((a, b)));
+    assert_eq!(Some(&2), dag.edge_weight(e_c));
+
+    dag.resource_edge(e_b, c).unwrap();
+    assert_eq!(dag.edge_endpoints(e_b), Some((c, b)));
+    assert_eq!(Some(&1), dag.edge_weight(e_b));
+
+    let err = dag.retarget_edge(e_c, root).unwrap_err();
+    assert_eq!(err.0, vec![root, a]);
+    assert_eq!(dag.edge_endpoints(e_c), Some((a, b)));
+    assert_eq!(dag.edge_endpoints(e_a), Some((root, a)));
+}
@@ -0,0 +1,137 @@
+extern crate daggy;
+
+use daggy::transaction::TransactionError;
+use daggy::{Dag, NodeIndex, Walker};
+
+type Adjacency = Vec<(Vec<usize>, Vec<usize>)>;
+type Snapshot = (Vec<&'static str>, Vec<(usize, usize, u32)>, Adjacency);
+
+// The children and parents of each node in the order in which they are visited.
+fn adjacency(dag: &Dag<&'static str, u32>) -> Adjacency {
+    dag.graph()
+        .node_indices()
+        .map(|n| {
+            let children = dag.children(n).iter(dag).map(|(e, _)| e.index()).collect();
+            let parents = dag.parents(n).iter(dag).map(|(e, _)| e.index()).collect();
+            (children, parents)
+        })
+        .collect()
+}
+
+fn snapshot(dag: &Dag<&'static str, u32>) -> Snapshot {
+    let nodes = dag.raw_nodes().iter().map(|n| n.weight).collect();
+    let edges = dag
+        .raw_edges()
+        .iter()
+        .map(|e| (e.source().index(), e.target().index(), e.weight))
+        .collect();
+    (nodes, edges, adjacency(dag))
+}
+
+fn example() -> (Dag<&'static str, u32>, Vec<NodeIndex>) {
+    let mut dag = Dag::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    let (_, c) = dag.add_child(a, 1, "c");
+    let (_, d) = dag.add_child(b, 2, "d");
+    dag.add_edge(c, d, 3).unwrap();
+    (dag, vec![a, b, c, d])
+}
+
+#[test]
+fn commit() {
+    let (mut dag, n) = example();
+    let e = dag
+        .transaction(|tx| -> Result<_, ()> {
+            let (_, x) = tx.add_child(n[3], 4, "x");
+            tx.set_node_weight(n[0], "A");
+            *tx.edge_weight_mut(daggy::EdgeIndex::new(0)).unwrap() = 10;
+            Ok(tx.add_edge(n[0], x, 5))
+        })
+        .unwrap();
+    assert_eq!(dag.node_count(), 5);
+    assert_eq!(dag[n[0]], "A");
+    assert_eq!(dag[daggy::EdgeIndex::new(0)], 10);
+    assert_eq!(dag[e], 5);
+}
+
+#[test]
+fn abort_restores_exact_state() {
+    let (mut dag, n) = example();
+    let before = snapshot(&dag);
+    let result = dag.transaction(|tx| {
+        tx.add_node("x");
+        tx.remove_node(n[1]).unwrap();
+        tx.remove_node(n[0]).unwrap();
+        tx.remove_edge(daggy::EdgeIndex::new(0)).unwrap();
+        let y = tx.add_node("y");
+        tx.update_edge(y, n[2], 6);
+        *tx.node_weight_mut(n[2]).unwrap() = "changed";
+        Err::<(), _>("abort")
+    });
+    match result {
+        Err(TransactionError::Aborted("abort")) => (),
+        _ => panic!("expected the transaction to abort"),
+    }
+    assert_eq!(snapshot(&dag), before);
+}
+
+#[test]
+fn deferred_cycle_check() {
+    let (mut dag, n) = example();
+    let before = snapshot(&dag);
+
+    // The transient cycle is broken before commit, so the transaction succeeds.
+    dag.transaction(|tx| -> Result<_, ()> {
+        let e = tx.add_edge(n[3], n[0], 7);
+        tx.remove_edge(e);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(snapshot(&dag), before);
+
+    let result = dag.transaction(|tx| -> Result<_, ()> {
+        tx.remove_edge(daggy::EdgeIndex::new(1)).unwrap();
+        tx.add_edge(n[3], n[0], 7);
+        Ok(())
+    });
+    match result {
+        Err(TransactionError::WouldCycle) => (),
+        _ => panic!("expected a cycle"),
+    }
+    assert_eq!(snapshot(&dag), before);
+}
+
+#[test]
+fn abort_restores_neighbour_order() {
+    let mut dag = Dag::<&'static str, u32>::new();
+    let a = dag.add_node("a");
+    let (_, b) = dag.add_child(a, 0, "b");
+    let (_, _c) = dag.add_child(a, 1, "c");
+    let (_, d) = dag.add_child(a, 2, "d");
+    dag.add_edge(b, d, 3).unwrap();
+    let children = |dag: &Dag<&'static str, u32>| -> Vec<usize> {
+        dag.children(a).iter(dag).map(|(_, n)| n.index()).collect()
+    };
+    assert_eq!(children(&dag), [3, 2, 1]);
+    let before = snapshot(&dag);
+
+    let result = dag.transaction(|tx| {
+        tx.remove_node(b).unwrap();
+        Err::<(), _>(())
+    });
+    assert!(result.is_err());
+    assert_eq!(children(&dag), [3, 2, 1]);
+    assert_eq!(snapshot(&dag), before);
+
+    let result = dag.transaction(|tx| {
+        let (_, x) = tx.add_child(d, 4, "x");
+        tx.remove_edge(daggy::EdgeIndex::new(1)).unwrap();
+        tx.add_edge(a, x, 5);
+        tx.remove_node(a).unwrap();
+        tx.remove_edge(daggy::EdgeIndex::new(0)).unwrap();
+        Err::<(), _>(())
+    });
+    assert!(result.is_err());
+    assert_eq!(snapshot(&dag), before);
+}